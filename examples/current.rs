@@ -0,0 +1,50 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use bevy::prelude::*;
+use mod_plugins::resources::*;
+
+fn main() {
+    let mut world = World::new();
+
+    // payloads do not need to be `Clone` or `Debug`, and can be changed in place
+    let ticket = world.with_current(Ticket(vec!["cube"]), |world| {
+        let mut current = world.resource_mut::<Current<Ticket>>();
+        current.0.push("sphere");
+        current.0.len()
+    });
+    assert_eq!(ticket, 2);
+    assert!(!world.contains_resource::<Current<Ticket>>());
+    println!("Current modified in place");
+
+    // nested calls restore the outer value once the inner one is done
+    world.with_current(Depth(1), |world| {
+        world.with_current(Depth(2), |world| {
+            assert_eq!(world.resource::<Current<Depth>>().0, 2);
+        });
+        assert_eq!(world.resource::<Current<Depth>>().0, 1);
+    });
+    assert!(!world.contains_resource::<Current<Depth>>());
+    println!("Nested current restored");
+
+    // a panic still removes the current, and restores the outer value
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(|_| {}));
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        world.with_current(Depth(3), |_| panic!("executable failed"));
+    }));
+    assert!(result.is_err());
+    assert!(!world.contains_resource::<Current<Depth>>());
+
+    world.with_current(Depth(4), |world| {
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            world.with_current(Depth(5), |_| panic!("executable failed"));
+        }));
+        assert!(result.is_err());
+        assert_eq!(world.resource::<Current<Depth>>().0, 4);
+    });
+    std::panic::set_hook(hook);
+    println!("Current removed after panic");
+}
+
+pub struct Ticket(pub Vec<&'static str>);
+
+pub struct Depth(pub u32);
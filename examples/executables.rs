@@ -26,11 +26,11 @@ pub struct Cube(pub Color);
 
 #[executable(Cube)]
 fn spawn_cube(
+    In(Cube(color)): In<Cube>,
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>
 ) {
-    let color = current.into_inner().clone().into_inner().0;
     commands.spawn((
         Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
         MeshMaterial3d(materials.add(color)),
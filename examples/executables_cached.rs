@@ -0,0 +1,32 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::{Current, Executable}};
+
+fn main() {
+    let mut world = World::new();
+    world.init_resource::<Log>();
+
+    // execute twice, the executables local should persist between executions
+    assert_eq!(Box::new(Count(10)).execute(&mut world), 1);
+    assert_eq!(Box::new(Count(20)).execute(&mut world), 2);
+
+    // the current executable should not be left in the world
+    assert!(!world.contains_resource::<Current<Box<Count>>>());
+    assert_eq!(world.resource::<Log>().0, vec![11, 22]);
+    println!("Log {:?}", world.resource::<Log>().0);
+}
+
+#[derive(Resource, Default)]
+pub struct Log(pub Vec<u32>);
+
+#[derive(Clone, Debug)]
+pub struct Count(pub u32);
+
+#[executable(Count)]
+fn count(
+    mut executions: Local<u32>,
+    mut log: ResMut<Log>
+) -> u32 {
+    *executions += 1;
+    log.0.push(current.0 + *executions);
+    *executions
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ExecutablePlugin))
+        .init_resource::<Rolls>()
+        .init_resource::<Results>();
+
+    // queued executables wait on a handle entity until the plugin runs them
+    let mut commands = app.world_mut().commands();
+    let completed = commands.queue_executable(Box::new(Roll(6)))
+        .observe(|trigger: Trigger<ExecutableCompleted<u32>>, mut results: ResMut<Results>| {
+            results.0.push(trigger.event().0);
+        })
+        .id();
+    let cancelled = commands.queue_executable(Box::new(Roll(4))).id();
+    app.world_mut().flush();
+    assert!(app.world().get::<PendingExecutable>(completed).is_some());
+    assert!(app.world().get::<PendingExecutable>(cancelled).is_some());
+    assert!(app.world().resource::<Rolls>().0.is_empty());
+
+    // despawning a handle before the next update cancels its executable
+    app.world_mut().despawn(cancelled);
+    app.update();
+    assert_eq!(app.world().resource::<Rolls>().0, vec![6]);
+    println!("Cancelled executable skipped");
+
+    // the observer of the handle receives the response, then the handle is despawned
+    assert_eq!(app.world().resource::<Results>().0, vec![12]);
+    assert!(app.world().get_entity(completed).is_err());
+    println!("Completed executable observed");
+}
+
+#[derive(Resource, Default)]
+pub struct Rolls(pub Vec<u32>);
+
+#[derive(Resource, Default)]
+pub struct Results(pub Vec<u32>);
+
+pub struct Roll(pub u32);
+
+#[executable(Roll)]
+fn roll(mut rolls: ResMut<Rolls>) -> u32 {
+    rolls.0.push(current.0);
+    current.0 * 2
+}
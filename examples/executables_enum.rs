@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut world = World::new();
+    world.init_resource::<Log>();
+
+    // each variant runs its own system with the fields of the variant as input
+    let actions: Vec<Box<Action>> = vec![
+        Box::new(Action::Spawn(2)),
+        Box::new(Action::Move { x: 1.0, y: 2.5 }),
+        Box::new(Action::Spawn(3)),
+        Box::new(Action::Reset)
+    ];
+    for action in actions {
+        world.execute(action);
+    }
+
+    // the system of each variant is cached, so its locals persist between executions
+    let log = &world.resource::<Log>().0;
+    println!("Log {log:?}");
+    assert_eq!(log, &["spawn 2 (1)", "move 1 2.5", "spawn 3 (2)", "reset"]);
+
+    // enums can be type erased with every other executable
+    let boxed: BoxedExecutable = Box::new(Action::Reset);
+    world.execute(boxed);
+    assert_eq!(world.resource::<Log>().0.len(), 5);
+}
+
+#[derive(Resource, Default)]
+pub struct Log(pub Vec<String>);
+
+// leaving out the function of a variant fails to compile with an error naming the variant
+#[executable]
+pub enum Action {
+    Spawn(u32),
+    Move { x: f32, y: f32 },
+    Reset
+}
+
+#[executable(Action::Spawn)]
+fn spawn(In(count): In<u32>, mut waves: Local<u32>, mut log: ResMut<Log>) {
+    *waves += 1;
+    log.0.push(format!("spawn {count} ({})", *waves));
+}
+
+#[executable(Action::Move)]
+fn move_to(In((x, y)): In<(f32, f32)>, mut log: ResMut<Log>) {
+    log.0.push(format!("move {x} {y}"));
+}
+
+#[executable(Action::Reset)]
+fn reset(mut log: ResMut<Log>) {
+    log.0.push("reset".to_string());
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut world = World::new();
+    world.init_resource::<Stock>();
+
+    // the executable is moved into its system, so it does not need to be `Clone` or `Debug`
+    let shipment = Shipment { crates: vec![Crate("apples"), Crate("pears")], label: Label("north") };
+    let count = world.execute(Box::new(shipment));
+    assert_eq!(count, 3);
+
+    // the system can change its input and move the fields out of it
+    let stock = &world.resource::<Stock>().0;
+    assert_eq!(stock.iter().map(|item| item.0).collect::<Vec<_>>(), ["apples", "pears", "spare"]);
+    assert_eq!(world.resource::<Stock>().1.as_ref().map(|label| label.0), Some("north"));
+
+    // the system is cached, and the next shipment is received by value too
+    let count = world.execute(Box::new(Shipment { crates: Vec::new(), label: Label("south") }));
+    assert_eq!(count, 1);
+    assert_eq!(world.resource::<Stock>().0.len(), 4);
+    assert_eq!(world.resource::<Stock>().1.as_ref().map(|label| label.0), Some("south"));
+    println!("Shipments received by value");
+}
+
+pub struct Crate(pub &'static str);
+
+pub struct Label(pub &'static str);
+
+pub struct Shipment {
+    pub crates: Vec<Crate>,
+    pub label: Label
+}
+
+#[derive(Resource, Default)]
+pub struct Stock(pub Vec<Crate>, pub Option<Label>);
+
+#[executable(Shipment)]
+fn receive(In(mut shipment): In<Shipment>, mut stock: ResMut<Stock>) -> usize {
+    shipment.crates.push(Crate("spare"));
+    let count = shipment.crates.len();
+    stock.0.extend(shipment.crates);
+    stock.1 = Some(shipment.label);
+    count
+}
@@ -0,0 +1,47 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, ExecutablePlugin))
+        .init_resource::<Log>()
+        .add_systems(First, |mut log: ResMut<Log>| log.0.push("first".to_string()))
+        .add_systems(PreUpdate, send_actions.before(drain_executable_queue))
+        .add_systems(Update, |mut log: ResMut<Log>| log.0.push("update".to_string()));
+
+    // executables of different types can wait in the queue together, for example when received from a server
+    let mut queue = app.world_mut().resource_mut::<ExecutableQueue>();
+    queue.push(Box::new(Spawn("cube")));
+    queue.extend([Box::new(Say("hello")) as BoxedExecutable, Box::new(Spawn("sphere"))]);
+    assert_eq!(queue.len(), 3);
+    app.update();
+
+    // commands run their executable as they are applied, then the queue is drained in order, all before `Update`
+    let log = &app.world().resource::<Log>().0;
+    println!("Log {log:?}");
+    assert_eq!(log, &["first", "say queued", "spawn cube", "say hello", "spawn sphere", "spawn late", "update"]);
+    assert!(app.world().resource::<ExecutableQueue>().is_empty());
+}
+
+#[derive(Resource, Default)]
+pub struct Log(pub Vec<String>);
+
+pub struct Spawn(pub &'static str);
+
+pub struct Say(pub &'static str);
+
+fn send_actions(mut commands: Commands, mut queue: ResMut<ExecutableQueue>) {
+    commands.execute(Box::new(Say("queued")));
+    queue.push(Box::new(Spawn("late")));
+}
+
+#[executable(Spawn)]
+fn spawn(mut commands: Commands, mut log: ResMut<Log>) {
+    commands.spawn_empty();
+    log.0.push(format!("spawn {}", current.0));
+}
+
+#[executable(Say)]
+fn say(mut log: ResMut<Log>) {
+    log.0.push(format!("say {}", current.0));
+}
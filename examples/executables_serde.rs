@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+use serde::{Deserialize, Serialize};
+
+fn main() {
+    // executables of a plugin are registered by it, others are registered by hand
+    let mut app = App::new();
+    app.add_plugins(Actions)
+        .register_serde_executable::<Rename>()
+        .register_serde_executable::<Spawn>();
+
+    // loop a list of actions back through each codec
+    for codec in [ExecutableCodec::Json, ExecutableCodec::Ron, ExecutableCodec::Bincode] {
+        let messages = [
+            SerializedExecutable::encode(codec, &Spawn { name: "cube".to_string(), count: 2 }).unwrap(),
+            SerializedExecutable::encode(codec, &Despawn(1)).unwrap(),
+            SerializedExecutable::encode(codec, &Rename("sphere".to_string())).unwrap()
+        ];
+
+        // decode and execute the actions against the world
+        let world = app.world_mut();
+        let actions = messages.iter()
+            .map(|message| world.resource::<SerdeExecutables>().decode(codec, message).unwrap())
+            .collect::<Vec<_>>();
+        for action in actions {
+            world.execute(action);
+        }
+    }
+
+    let log = &app.world().resource::<Log>().0;
+    println!("Log {log:?}");
+    assert_eq!(log.len(), 9);
+    assert_eq!(log[0], "spawn 2 cube");
+    assert_eq!(log[1], "despawn 1");
+    assert_eq!(log[2], "rename sphere");
+
+    // unknown tags are reported instead of panicking
+    let unknown = SerializedExecutable { tag: "Unknown".to_string(), bytes: vec![] };
+    assert!(matches!(
+        app.world().resource::<SerdeExecutables>().decode(ExecutableCodec::Json, &unknown),
+        Err(ExecutableCodecError::UnknownTag(_))
+    ));
+
+    // two types can not share a tag, as messages would be decoded into the wrong one
+    let duplicate = std::panic::catch_unwind(|| {
+        let mut executables = SerdeExecutables::default();
+        executables.register::<Despawn>();
+        executables.register::<Retag>();
+    });
+    assert!(duplicate.is_err());
+}
+
+#[plugin]
+mod actions {
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Log(pub Vec<String>);
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Spawn {
+        pub name: String,
+        pub count: u32
+    }
+
+    #[derive(Clone, Debug, Serialize, Deserialize)]
+    pub struct Despawn(pub u32);
+
+    #[executable(Spawn, serde)]
+    fn spawn(mut log: ResMut<Log>) {
+        log.0.push(format!("spawn {} {}", current.count, current.name));
+    }
+
+    #[executable(Despawn, serde = "despawn")]
+    fn despawn(mut log: ResMut<Log>) {
+        log.0.push(format!("despawn {}", current.0));
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Rename(pub String);
+
+#[executable(Rename, serde)]
+fn rename(mut log: ResMut<Log>) {
+    log.0.push(format!("rename {}", current.0));
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Retag(pub u32);
+
+#[executable(Retag, serde = "despawn")]
+fn retag() {}
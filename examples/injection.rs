@@ -0,0 +1,80 @@
+use bevy::prelude::*;
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Scoring))
+        .init_resource::<Assets<Level>>();
+    for _ in 0..3 {
+        app.update();
+    }
+
+    // locals start at their given value and keep it between runs
+    let world = app.world();
+    assert_eq!(world.resource::<Score>().0, 10 + 11 + 12);
+    assert_eq!(world.resource::<Ticks>().0, 3);
+    println!("Score counted");
+
+    // parameters named by hand do not collide with the defaults of other parameters
+    assert_eq!(world.resource::<Best>().0, 33);
+    assert!(world.resource::<Best>().1);
+
+    // commands and assets are injected like any other parameter
+    assert_eq!(world.resource::<Assets<Level>>().len(), 3);
+    let mut markers = app.world_mut().query::<&Marker>();
+    assert_eq!(markers.iter(app.world()).count(), 3);
+    println!("Entities and assets added");
+}
+
+#[plugin]
+mod scoring {
+    #[derive(Asset, TypePath)]
+    pub struct Level(pub u32);
+
+    #[derive(Component)]
+    pub struct Marker;
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Score(pub u32);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Ticks(pub u32);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Best(pub u32, pub bool);
+
+    #[update]
+    #[res_mut(Score)]
+    #[res_mut(Ticks)]
+    #[local(bonus: u32 = 10)]
+    #[local(runs: u32)]
+    fn count() {
+        score.0 += *bonus + *runs;
+        *runs += 1;
+        ticks.0 = *runs;
+    }
+
+    // `Time<Virtual>` would also be named `time`, which fails to compile, so it is named by hand
+    #[update]
+    #[after(count)]
+    #[res(Score)]
+    #[res(Time)]
+    #[res(virtual_time: Time<Virtual>)]
+    #[res_mut(best: Best)]
+    fn record() {
+        best.0 = score.0;
+        best.1 = virtual_time.elapsed() == time.elapsed();
+    }
+
+    #[update]
+    #[commands(spawner)]
+    #[assets(Level)]
+    #[res(Ticks)]
+    fn add_level() {
+        spawner.spawn(Marker);
+        levels.add(Level(ticks.0));
+    }
+}
@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Movement));
+
+    let world = app.world_mut();
+    let runner = world.spawn((Position(0), Velocity(2), Health(40), Armor(4))).id();
+    let walker = world.spawn((Position(1), Velocity(1), Health(60))).id();
+    let statue = world.spawn((Position(2), Velocity(0), Health(40), Armor(9))).id();
+    let frozen = world.spawn((Position(5), Velocity(3), Frozen)).id();
+    let far = world.spawn((Position(9), Target)).id();
+    world.spawn((Position(7), Target));
+    app.update();
+
+    // parallel iteration returns to skip an item, as the body runs in a closure
+    let world = app.world();
+    assert_eq!(world.get::<Position>(runner), Some(&Position(2)));
+    assert_eq!(world.get::<Position>(walker), Some(&Position(2)));
+    assert_eq!(world.get::<Position>(statue), Some(&Position(2)));
+    assert_eq!(world.get::<Position>(frozen), Some(&Position(5)));
+
+    // pairs are visited once each, so the three movers sharing a position collide three times
+    assert_eq!(world.resource::<Collisions>().0, 3);
+    println!("Movers moved and collided");
+
+    // joined queries only visit the entities matching all of them, then sequential iteration continues past healthy items
+    assert_eq!(world.get::<Health>(runner), Some(&Health(39)));
+    assert_eq!(world.get::<Health>(statue), Some(&Health(44)));
+    assert_eq!(world.get::<Health>(walker), Some(&Health(60)));
+
+    // return leaves the whole system, so no target is visited after the one found
+    assert_eq!(world.resource::<Search>().visited.last(), Some(&9));
+    assert_eq!(world.resource::<Search>().found, Some(far));
+    println!("Damage dealt and target found");
+}
+
+#[plugin]
+mod movement {
+    #[derive(Component, Debug, PartialEq)]
+    pub struct Position(pub i32);
+
+    #[derive(Component)]
+    pub struct Velocity(pub i32);
+
+    #[derive(Component, Debug, PartialEq)]
+    pub struct Health(pub u32);
+
+    #[derive(Component)]
+    pub struct Armor(pub u32);
+
+    #[derive(Component)]
+    pub struct Frozen;
+
+    #[derive(Component)]
+    pub struct Target;
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Collisions(pub u32);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Search {
+        pub visited: Vec<i32>,
+        pub found: Option<Entity>
+    }
+
+    #[update]
+    #[query(movers: (&mut Position, &Velocity), Without<Frozen>)]
+    #[on(par mut movers as (mut position, velocity))]
+    fn move_all() {
+        if velocity.0 == 0 { return }
+        position.0 += velocity.0;
+    }
+
+    #[update]
+    #[after(move_all)]
+    #[query(positions: &Position, With<Velocity>, Without<Frozen>)]
+    #[on(pairs positions as [a, b])]
+    #[res_mut(Collisions)]
+    fn collide() {
+        if a.0 == b.0 { collisions.0 += 1; }
+    }
+
+    #[update]
+    #[query(armors: &Armor)]
+    #[query(healths: &mut Health)]
+    #[on(armors, mut healths as (armor, mut health))]
+    fn damage() {
+        health.0 -= 10 - armor.0;
+    }
+
+    #[update]
+    #[after(damage)]
+    #[query(healths: &mut Health)]
+    #[on(mut healths as mut health)]
+    fn heal() {
+        if health.0 >= 40 { continue; }
+        health.0 += 5;
+    }
+
+    #[update]
+    #[query(targets: (Entity, &Position), With<Target>)]
+    #[on(targets as (target, position))]
+    #[res_mut(Search)]
+    fn search() {
+        search.visited.push(position.0);
+        if position.0 < 8 { continue; }
+        search.found = Some(target);
+        return;
+    }
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    // the manifests are only collected once the resource exists
+    let mut app = App::new();
+    app.init_resource::<PluginManifests>()
+        .add_plugins((MinimalPlugins, bevy::state::app::StatesPlugin, Arena));
+
+    let manifests = app.world().resource::<PluginManifests>();
+    println!("{}", manifests.to_json());
+
+    let manifest = manifests.get("Arena").expect("Arena manifest was not collected.");
+    assert_eq!(manifest.states, ["Round"]);
+    assert_eq!(manifest.events, ["Scored"]);
+    assert_eq!(manifest.resources, ["Score", "Timer"]);
+
+    let tick = manifest.systems.iter().find(|system| system.name == "tick").unwrap();
+    assert_eq!(tick.schedule, "Update");
+    assert_eq!(tick.run_conditions, ["in_state(Round::Playing)"]);
+    assert_eq!(Arena::MANIFEST.observers, ["reset_score"]);
+}
+
+#[plugin]
+mod arena {
+    #[init_state]
+    pub enum Round {
+        #[default]
+        Playing,
+        Over
+    }
+
+    #[init_event]
+    #[derive(Event)]
+    pub struct Scored(pub u32);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Score(pub u32);
+
+    #[derive(Resource)]
+    pub struct Timer(pub f32);
+
+    #[resource_factory]
+    fn timer() -> Timer {
+        Timer(60.0)
+    }
+
+    #[update]
+    #[state(Round::Playing)]
+    fn tick(mut timer: ResMut<Timer>, time: Res<Time>) {
+        timer.0 -= time.delta_secs();
+    }
+
+    #[update]
+    #[after(tick)]
+    #[event(Scored)]
+    fn add_score(mut score: ResMut<Score>) {
+        score.0 += scored.0;
+    }
+
+    #[derive(Component)]
+    pub struct Player;
+
+    #[trigger(OnRemove, Player)]
+    fn reset_score(mut score: ResMut<Score>) {
+        score.0 = 0;
+    }
+}
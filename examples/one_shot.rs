@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Waves));
+    app.update();
+
+    // one shot systems only run when asked, here by the update system
+    let world = app.world_mut();
+    assert_eq!(world.resource::<Spawned>().0, 10);
+    let mut enemies = world.query::<&Enemy>();
+    assert_eq!(enemies.iter(world).count(), 10);
+    println!("Wave spawned");
+
+    // the ids are stored in the plugin resource, and systems taking input return their output
+    let systems = world.resource::<WavesSystems>();
+    let (spawn_wave, count_enemies, clear) = (systems.spawn_wave, systems.count_enemies, systems.clear);
+    world.run_system_with_input(spawn_wave, 5).unwrap();
+    assert_eq!(world.run_system_with_input(count_enemies, 2).unwrap(), 30);
+
+    // exclusive one shot systems take the world
+    world.run_system(clear).unwrap();
+    assert_eq!(world.run_system_with_input(count_enemies, 1).unwrap(), 0);
+    assert_eq!(world.resource::<Spawned>().0, 15);
+    println!("Wave cleared");
+}
+
+#[plugin]
+mod waves {
+    #[derive(Component)]
+    pub struct Enemy;
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Spawned(pub u32);
+
+    #[one_shot]
+    fn spawn_wave(In(size): In<u32>, mut commands: Commands, mut spawned: ResMut<Spawned>) {
+        for _ in 0..size {
+            commands.spawn(Enemy);
+        }
+        spawned.0 += size;
+    }
+
+    #[one_shot]
+    fn count_enemies(In(scale): In<usize>, enemies: Query<&Enemy>) -> usize {
+        enemies.iter().count() * scale
+    }
+
+    #[one_shot]
+    fn clear(world: &mut World) {
+        let enemies = world.query_filtered::<Entity, With<Enemy>>().iter(world).collect::<Vec<_>>();
+        for enemy in enemies {
+            world.despawn(enemy);
+        }
+    }
+
+    #[update]
+    #[local(started: bool)]
+    fn first_wave(mut commands: Commands, systems: Res<WavesSystems>) {
+        if *started { return }
+        *started = true;
+        commands.run_system_with_input(systems.spawn_wave, 10);
+    }
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, AssetPlugin::default(), Enemies));
+
+    // wait for the enemy to receive the components from its file
+    for _ in 0..100 {
+        app.update();
+        if app.world_mut().query::<&Health>().iter(app.world()).next().is_some() { break }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    let (entity, instance, health, speed, transform) = app.world_mut()
+        .query::<(Entity, &PrefabInstance<Enemy>, &Health, &Speed, &Transform)>()
+        .single(app.world());
+    println!("Enemy {health:?} {speed:?} at {}", transform.translation);
+    assert_eq!(*health, Health(7));
+    assert_eq!(*speed, Speed(2.5));
+    let handle = instance.handle.clone();
+
+    // change the speed at runtime, then change the prefab as a hot reload would
+    app.world_mut().get_mut::<Speed>(entity).unwrap().0 = 9.0;
+    app.update();
+    let mut enemies = app.world_mut().resource_mut::<Assets<Enemy>>();
+    let enemy = enemies.get_mut(&handle).unwrap();
+    enemy.health = Health(20);
+    enemy.speed = Speed(1.0);
+    enemy.transform = Transform::from_xyz(0.0, 5.0, 0.0);
+
+    // asset events are sent at the end of the frame, so the change is applied in the next one
+    app.update();
+    app.update();
+
+    // components that were not changed at runtime follow the prefab, the rest are kept
+    let world = app.world();
+    assert_eq!(world.get::<Health>(entity), Some(&Health(20)));
+    assert_eq!(world.get::<Transform>(entity).unwrap().translation.y, 5.0);
+    assert_eq!(world.get::<Speed>(entity), Some(&Speed(9.0)));
+    println!("Enemy reloaded, keeping its runtime speed");
+
+    // prefabs that fail to load are reported once and no longer waited on
+    let missing = app.world().resource::<AssetServer>().load::<Enemy>("missing.prefab.ron");
+    let missing = app.world_mut().commands().spawn_prefab(missing).id();
+    for _ in 0..100 {
+        app.update();
+        if app.world().get::<PrefabInstance<Enemy>>(missing).unwrap().is_failed() { break }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+    assert!(app.world().get::<PrefabInstance<Enemy>>(missing).unwrap().is_failed());
+    assert!(app.world().get::<Health>(missing).is_none());
+}
+
+#[plugin]
+mod enemies {
+    #[register]
+    #[derive(Component, Reflect, Debug, PartialEq, Default)]
+    #[reflect(Component)]
+    pub struct Health(pub u32);
+
+    #[register]
+    #[derive(Component, Reflect, Debug, PartialEq, Default)]
+    #[reflect(Component)]
+    pub struct Speed(pub f32);
+
+    #[prefab(asset)]
+    pub struct Enemy {
+        pub health: Health,
+        pub transform: Transform,
+        #[default(Speed(2.5))]
+        pub speed: Speed
+    }
+
+    #[startup]
+    fn spawn_enemy(mut commands: Commands, assets: Res<AssetServer>) {
+        commands.spawn_prefab(assets.load::<Enemy>("enemy.prefab.ron"));
+    }
+}
@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Torches));
+
+    // fields without a default are given to `new`, every other field is filled by its default
+    let crate_prefab = CratePrefab::new(Health(5)).with_transform(Transform::from_xyz(1.0, 0.0, 0.0));
+    assert_eq!(crate_prefab.health, Health(5));
+    assert_eq!(crate_prefab.weight, Weight(20));
+    let entity = app.world_mut().spawn(crate_prefab).id();
+    assert_eq!(app.world().get::<Transform>(entity).unwrap().translation.x, 1.0);
+
+    // components without a `Default`, like handles, can be given to `new`, and defaults can be any expression
+    let sign = SignPrefab::new(Label("Exit"));
+    assert_eq!(sign.label, Label("Exit"));
+    assert_eq!(sign.transform.translation.y, 0.5);
+    let sign = sign.with_label(Label("Entry"));
+    assert_eq!(sign.label, Label("Entry"));
+
+    // tuple prefabs take their fields without a default in order and are replaced by index
+    let tagged = TaggedPrefab::new(Health(3)).with_1(Weight(7));
+    assert_eq!(tagged.0, Health(3));
+    assert_eq!(tagged.1, Weight(7));
+    assert_eq!(TaggedPrefab(Health(1), Weight(1)).1, Weight(1));
+
+    // tuple prefabs with a default for every field are defaulted
+    let weighted = WeightedPrefab::default();
+    assert_eq!(weighted.0, Weight(50));
+    assert_eq!(WeightedPrefab::new().with_0(Weight(2)).0, Weight(2));
+
+    // unit prefabs are defaulted and spawned like any other prefab
+    let entity = app.world_mut().spawn(MarkerPrefab::new()).id();
+    assert!(app.world().get_entity(entity).is_ok());
+    println!("Prefabs constructed");
+
+    // prefabs with spawn observers hold a hidden marker, so their own fields are untouched
+    let torch = TorchPrefab::new(Health(9)).with_marker(Weight(1));
+    app.world_mut().commands().spawn(torch);
+    app.world_mut().commands().spawn(LanternPrefab::new(Health(4)));
+    app.update();
+    assert_eq!(app.world().resource::<Lit>().0, 2);
+    println!("Prefab spawns observed");
+}
+
+#[derive(Component, Debug, Default, PartialEq)]
+pub struct Health(pub u32);
+
+#[derive(Component, Debug, Default, PartialEq)]
+pub struct Weight(pub u32);
+
+#[prefab]
+pub struct CratePrefab {
+    pub health: Health,
+    #[default(Weight(20))]
+    pub weight: Weight,
+    #[default(Transform::IDENTITY)]
+    pub transform: Transform
+}
+
+#[derive(Component, Debug, PartialEq)]
+pub struct Label(pub &'static str);
+
+#[prefab]
+pub struct SignPrefab {
+    pub label: Label,
+    #[default(Transform::from_xyz(0.0, 0.5, 0.0))]
+    pub transform: Transform
+}
+
+#[prefab]
+pub struct TaggedPrefab(pub Health, #[default(Weight(1))] pub Weight);
+
+#[prefab]
+pub struct WeightedPrefab(#[default(Weight(50))] pub Weight);
+
+#[prefab]
+pub struct MarkerPrefab;
+
+#[plugin]
+mod torches {
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Lit(pub u32);
+
+    #[prefab]
+    pub struct TorchPrefab {
+        pub health: Health,
+        #[default(Weight(0))]
+        pub marker: Weight
+    }
+
+    impl TorchPrefab {
+        #[on_spawn]
+        fn light_torch(mut lit: ResMut<Lit>) {
+            lit.0 += 1;
+        }
+    }
+
+    #[prefab]
+    pub struct LanternPrefab(pub Health);
+
+    impl LanternPrefab {
+        #[on_spawn]
+        fn light_lantern(mut lit: ResMut<Lit>) {
+            lit.0 += 1;
+        }
+    }
+}
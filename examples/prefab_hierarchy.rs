@@ -0,0 +1,99 @@
+use bevy::{prelude::*, state::app::StatesPlugin};
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, Garage));
+    app.update();
+
+    // children are spawned under the root of the prefab
+    let mut commands = app.world_mut().commands();
+    let car = CarPrefab::default()
+        .with_wheels((0..4).map(|_| WheelPrefab::default()).collect())
+        .with_headlight(Some(LightPrefab::default()))
+        .spawn(&mut commands);
+    app.world_mut().flush();
+
+    let world = app.world_mut();
+    let children = world.get::<Children>(car).unwrap().to_vec();
+    assert_eq!(children.len(), 5);
+    let wheels = world.query_filtered::<Entity, With<Wheel>>().iter(world).collect::<Vec<_>>();
+    let light = world.query_filtered::<Entity, With<Light>>().single(world);
+    assert_eq!(wheels.len(), 4);
+    assert!(wheels.iter().all(|wheel| children.contains(wheel)));
+
+    // the scope of the root is given to its children, unless they have their own
+    assert!(wheels.iter().all(|wheel| world.get::<ScopeLocal<Level>>(*wheel).is_some_and(|scope| scope.0 == Level::First)));
+    assert!(world.get::<ScopeGlobal>(light).is_some());
+    assert!(world.get::<ScopeLocal<Level>>(light).is_none());
+
+    // the bolts of each wheel are descendants, so they get the scope too
+    let bolts = world.query_filtered::<Entity, With<Bolt>>().iter(world).collect::<Vec<_>>();
+    assert_eq!(bolts.len(), 8);
+    assert!(bolts.iter().all(|bolt| world.get::<ScopeLocal<Level>>(*bolt).is_some()));
+    println!("Prefab hierarchy spawned");
+
+    // leaving the state despawns the hierarchy, apart from the global light
+    app.world_mut().resource_mut::<NextState<Level>>().set(Level::Second);
+    app.update();
+    let world = app.world();
+    assert!(world.get_entity(car).is_err());
+    assert!(wheels.iter().chain(bolts.iter()).all(|entity| world.get_entity(*entity).is_err()));
+    assert!(world.get_entity(light).is_ok());
+    println!("Prefab hierarchy cleaned up");
+}
+
+#[plugin]
+mod garage {
+    #[init_state]
+    pub enum Level {
+        #[default]
+        First,
+        Second
+    }
+
+    #[derive(Component, Default)]
+    pub struct Car;
+
+    #[derive(Component, Default)]
+    pub struct Wheel;
+
+    #[derive(Component, Default)]
+    pub struct Bolt;
+
+    #[derive(Component, Default)]
+    pub struct Light;
+
+    #[prefab]
+    pub struct BoltPrefab {
+        #[default(Bolt)]
+        pub bolt: Bolt
+    }
+
+    #[prefab]
+    pub struct WheelPrefab {
+        #[default(Wheel)]
+        pub wheel: Wheel,
+        #[child]
+        #[default(vec![BoltPrefab::default(), BoltPrefab::default()])]
+        pub bolts: Vec<BoltPrefab>
+    }
+
+    #[prefab(scope = global)]
+    pub struct LightPrefab {
+        #[default(Light)]
+        pub light: Light
+    }
+
+    #[prefab(scope = local(Level))]
+    pub struct CarPrefab {
+        #[default(Car)]
+        pub car: Car,
+        #[child]
+        #[default(Vec::new())]
+        pub wheels: Vec<WheelPrefab>,
+        #[child]
+        #[default(None)]
+        pub headlight: Option<LightPrefab>
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::{prelude::*, state::{app::StatesPlugin, state::FreelyMutableState}};
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, Sky));
+    app.update();
+
+    // tuple prefabs hold their scope as a last field, and unit prefabs become tuples holding it
+    let world = app.world_mut();
+    let storm = world.spawn(CloudPrefab::default()).id();
+    let drizzle = world.spawn(CloudPrefab::default().with_scope((ScopeLocal(Level::Second), ScopeLocal(Weather::Clear)))).id();
+    let sun = world.spawn(SunPrefab::default()).id();
+    assert!(world.get::<ScopeLocal<Level>>(storm).is_some_and(|scope| scope.0 == Level::First));
+    assert!(world.get::<ScopeLocal<Weather>>(storm).is_some_and(|scope| scope.0 == Weather::Rain));
+    assert!(world.get::<ScopeGlobal>(sun).is_some());
+
+    // entities scoped to several states are despawned as soon as any of them is exited
+    set_state(&mut app, Weather::Clear);
+    assert!(app.world().get_entity(storm).is_err());
+    assert!(app.world().get_entity(drizzle).is_ok());
+    set_state(&mut app, Level::Second);
+    assert!(app.world().get_entity(drizzle).is_ok());
+    set_state(&mut app, Level::First);
+    assert!(app.world().get_entity(drizzle).is_err());
+    assert!(app.world().get_entity(sun).is_ok());
+    println!("Scoped prefabs cleaned up");
+}
+
+fn set_state<S: FreelyMutableState>(app: &mut App, state: S) {
+    app.world_mut().resource_mut::<NextState<S>>().set(state);
+    app.update();
+}
+
+#[plugin]
+mod sky {
+    #[init_state]
+    pub enum Level {
+        #[default]
+        First,
+        Second
+    }
+
+    #[init_state]
+    pub enum Weather {
+        Clear,
+        #[default]
+        Rain
+    }
+
+    #[derive(Component, Default)]
+    pub struct Cloud;
+
+    #[prefab(scope = local(Level, Weather))]
+    pub struct CloudPrefab(#[default(Cloud)] pub Cloud);
+
+    #[prefab(scope = global)]
+    pub struct SunPrefab;
+}
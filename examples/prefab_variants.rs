@@ -0,0 +1,98 @@
+use bevy::prelude::*;
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Enemies));
+
+    // variants hold the fields of their base, with their overrides as defaults
+    let boss = BossPrefab::default();
+    assert_eq!(boss.health, Health(500));
+    assert_eq!(boss.speed, Speed(3));
+    let boss = boss.with_speed(Speed(1)).with_crown(Crown(2));
+    let elite = ElitePrefab::new(Title("Duke")).with_health(Health(50));
+    assert_eq!(elite.crown, Crown(1));
+    let grunt = GruntPrefab::default();
+    assert_eq!(grunt.health, Health(10));
+
+    let world = app.world_mut();
+    let boss = world.spawn(boss).id();
+    let elite = world.spawn(elite).id();
+    let grunt = world.spawn(grunt).id();
+    world.flush();
+    assert_eq!(world.get::<Speed>(boss), Some(&Speed(1)));
+    assert_eq!(world.get::<Health>(elite), Some(&Health(50)));
+    assert_eq!(world.get::<Title>(elite), Some(&Title("Duke")));
+
+    // spawn observers of the base also run for its variants
+    assert_eq!(world.resource::<Spawned>().0, 3);
+    assert!(world.get::<Alerted>(grunt).is_some());
+    println!("Prefab variants spawned");
+
+    // nested prefabs are overridden through `#[overrides]`, while Bevy still sees `#[bundle(ignore)]`
+    let squad = world.spawn(SquadPrefab::default()).id();
+    assert_eq!(world.get::<Speed>(squad), Some(&Speed(9)));
+    assert_eq!(world.get::<Health>(squad), Some(&Health(10)));
+    println!("Nested prefab spawned");
+}
+
+#[plugin]
+mod enemies {
+    #[derive(Component, Debug, Default, PartialEq)]
+    pub struct Health(pub u32);
+
+    #[derive(Component, Debug, Default, PartialEq)]
+    pub struct Speed(pub u32);
+
+    #[derive(Component, Debug, Default, PartialEq)]
+    pub struct Crown(pub u32);
+
+    #[derive(Component, Debug, PartialEq)]
+    pub struct Title(pub &'static str);
+
+    #[derive(Component)]
+    pub struct Alerted;
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Spawned(pub u32);
+
+    #[prefab]
+    pub struct EnemyPrefab {
+        #[default(Health(10))]
+        pub health: Health,
+        #[default(Speed(3))]
+        pub speed: Speed
+    }
+
+    impl EnemyPrefab {
+        #[on_spawn]
+        fn alert(mut commands: Commands, mut spawned: ResMut<Spawned>) {
+            spawned.0 += 1;
+            commands.entity(trigger.entity()).insert(Alerted);
+        }
+    }
+
+    #[prefab(extends = EnemyPrefab { health: Health(500) })]
+    pub struct BossPrefab {
+        #[default(Crown(1))]
+        pub crown: Crown
+    }
+
+    #[prefab(extends = BossPrefab { speed: Speed(5) })]
+    pub struct ElitePrefab {
+        pub title: Title
+    }
+
+    #[prefab(extends = EnemyPrefab)]
+    pub struct GruntPrefab;
+
+    #[prefab]
+    pub struct SquadPrefab {
+        #[overrides(speed: Speed(9))]
+        pub leader: EnemyPrefab,
+        #[bundle(ignore)]
+        #[default(Vec::new())]
+        pub orders: Vec<String>
+    }
+}
@@ -33,15 +33,31 @@ mod test_plugin {
         DoesNotExist 
     }
 
-    #[prefab(scope local CubeState)]
+    #[prefab(scope = local(CubeState))]
     pub struct CubePrefab {
         pub mesh: Mesh3d,
         pub material: MeshMaterial3d<StandardMaterial>,
+        #[default(Transform::from_xyz(0.0, 0.5, 0.0))]
         pub transform: Transform,
+        #[default(GlobalTransform::IDENTITY)]
         pub global_transform: GlobalTransform,
+        #[default(Visibility::Inherited)]
         pub visibility: Visibility,
+        #[default(InheritedVisibility::VISIBLE)]
         pub inherited_visibility: InheritedVisibility,
-        pub view_visibility: ViewVisibility
+        #[default(ViewVisibility::HIDDEN)]
+        pub view_visibility: ViewVisibility,
+        #[child]
+        #[default(GlowPrefab::default())]
+        pub glow: GlowPrefab
+    }
+
+    #[prefab]
+    pub struct GlowPrefab {
+        #[default(PointLight { intensity: 10_000.0, color: Color::srgb_u8(124, 144, 255), ..default() })]
+        pub light: PointLight,
+        #[default(Transform::from_xyz(0.0, 1.0, 0.0))]
+        pub transform: Transform
     }
 
     #[startup]
@@ -80,13 +96,10 @@ mod test_plugin {
         mut meshes: ResMut<Assets<Mesh>>,
         mut materials: ResMut<Assets<StandardMaterial>>
     ) {
-        commands.spawn(CubePrefab {
-            mesh: Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
-            material: MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255))),
-            transform: Transform::from_xyz(0.0, 0.5, 0.0),
-            scope: ScopeLocal(CubeState::Exists),
-            ..Default::default()
-        });
+        CubePrefab::new(
+            Mesh3d(meshes.add(Cuboid::new(1.0, 1.0, 1.0))),
+            MeshMaterial3d(materials.add(Color::srgb_u8(124, 144, 255)))
+        ).with_scope(ScopeLocal(CubeState::Exists)).spawn(&mut commands);
     }
 
     #[event(KeyboardInput)]
@@ -0,0 +1,107 @@
+use bevy::prelude::*;
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Arena));
+
+    let world = app.world_mut();
+    world.spawn((Player, Position(0)));
+    let wolf = world.spawn((Enemy, Position(4), Shield)).id();
+    let bat = world.spawn((Enemy, Position(10), Flying)).id();
+    let rock = world.spawn(Position(2)).id();
+    app.update();
+
+    // `Single` runs with the one player, `Option` and `Has` see components that may be missing
+    let report = app.world().resource::<Report>();
+    assert_eq!(report.nearest, Some(4));
+    assert_eq!(report.shielded, 1);
+
+    // `Or` filters and `AnyOf` data match entities with either component
+    assert_eq!(report.threats, 2);
+    assert_eq!(report.marked, 2);
+    println!("Queries read");
+
+    // mutable data makes the query mutable, and unnamed queries are named in order
+    let world = app.world();
+    assert_eq!(world.get::<Position>(wolf), Some(&Position(3)));
+    assert_eq!(world.get::<Position>(bat), Some(&Position(9)));
+    assert_eq!(world.get::<Position>(rock), Some(&Position(2)));
+    assert_eq!(world.resource::<Report>().positions, 4);
+    assert_eq!(world.resource::<Report>().changed, 4);
+
+    // only the moved enemies have changed since the last run
+    app.update();
+    let world = app.world();
+    assert_eq!(world.get::<Position>(wolf), Some(&Position(2)));
+    assert_eq!(world.resource::<Report>().nearest, Some(3));
+    assert_eq!(world.resource::<Report>().changed, 2);
+    println!("Changes detected");
+}
+
+#[plugin]
+mod arena {
+    #[derive(Component)]
+    pub struct Player;
+
+    #[derive(Component)]
+    pub struct Enemy;
+
+    #[derive(Component)]
+    pub struct Shield;
+
+    #[derive(Component)]
+    pub struct Flying;
+
+    #[derive(Component, Debug, PartialEq)]
+    pub struct Position(pub i32);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Report {
+        pub nearest: Option<i32>,
+        pub shielded: u32,
+        pub positions: u32,
+        pub threats: u32,
+        pub marked: u32,
+        pub changed: u32
+    }
+
+    #[update]
+    #[query(player: Single<&Position, With<Player>>)]
+    #[query(enemies: (&Position, Option<&Shield>, Has<Flying>), With<Enemy>)]
+    #[res_mut(Report)]
+    fn scout() {
+        report.nearest = enemies.iter()
+            .map(|(position, _, _)| (position.0 - player.0).abs())
+            .min();
+        report.shielded = enemies.iter().filter(|(_, shield, flying)| shield.is_some() && !flying).count() as u32;
+    }
+
+    #[update]
+    #[query(threats: Entity, Or<(With<Shield>, With<Flying>)>)]
+    #[query(marked: AnyOf<(&Shield, &Flying)>)]
+    #[res_mut(Report)]
+    fn count_threats() {
+        report.threats = threats.iter().count() as u32;
+        report.marked = marked.iter().count() as u32;
+    }
+
+    #[update]
+    #[after(scout)]
+    #[query(enemies: Populated<&mut Position, With<Enemy>>)]
+    fn approach() {
+        for mut position in enemies.iter_mut() {
+            position.0 -= 1;
+        }
+    }
+
+    #[update]
+    #[after(approach)]
+    #[query(Ref<Position>)]
+    #[res_mut(Report)]
+    fn watch() {
+        report.positions = query1.iter().count() as u32;
+        report.changed = query1.iter().filter(|position| position.is_changed()).count() as u32;
+    }
+}
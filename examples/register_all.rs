@@ -0,0 +1,65 @@
+use bevy::{prelude::*, state::app::StatesPlugin};
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, Inventory));
+
+    let registry = app.world().resource::<AppTypeRegistry>().read();
+    let registered = |path: &str| registry.get_with_type_path(path).is_some();
+    for ty in Inventory::MANIFEST.registered {
+        println!("Registered {ty}");
+    }
+
+    // every reflected type is registered, generic types only as the listed instantiations
+    assert!(registered("register_all::Item"));
+    assert!(registered("register_all::Rarity"));
+    assert!(registered("register_all::Stack<u32>"));
+    assert!(registered("register_all::Stack<register_all::Item>"));
+
+    // events deriving reflect are registered, while types that do not derive it are left out
+    assert!(registered("register_all::ItemPicked"));
+    assert!(!registered("register_all::Cache"));
+
+    // states are reflected along with their resources
+    assert!(registered("register_all::InventoryState"));
+    assert!(registered("bevy_state::state::resources::State<register_all::InventoryState>"));
+    assert!(registered("bevy_state::state::resources::NextState<register_all::InventoryState>"));
+}
+
+#[plugin(register_all)]
+mod inventory {
+    #[derive(Component, Reflect, Default)]
+    #[reflect(Component)]
+    pub struct Item {
+        pub name: String
+    }
+
+    #[derive(Component, Reflect, Default)]
+    pub enum Rarity {
+        #[default]
+        Common,
+        Rare
+    }
+
+    #[register(Stack<u32>, Stack<Item>)]
+    #[derive(Component, Reflect, Default)]
+    pub struct Stack<T: Reflect + TypePath + Default> {
+        pub items: Vec<T>
+    }
+
+    #[init_event]
+    #[derive(Event, Reflect)]
+    pub struct ItemPicked(pub Entity);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Cache(pub Vec<Entity>);
+
+    #[init_state]
+    pub enum InventoryState {
+        #[default]
+        Closed,
+        Open
+    }
+}
@@ -0,0 +1,59 @@
+use bevy::prelude::*;
+use mod_plugins::macros::*;
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Enemies));
+
+    // missing required components are inserted with the marked component, given values win over defaults
+    let world = app.world_mut();
+    let enemy = world.spawn(Enemy).id();
+    assert_eq!(world.get::<Health>(enemy), Some(&Health(100)));
+    assert_eq!(world.get::<Team>(enemy), Some(&Team::Hostile));
+    assert!(world.get::<Transform>(enemy).is_some());
+
+    // components that are already given are kept
+    let tough = world.spawn((Enemy, Health(250))).id();
+    assert_eq!(world.get::<Health>(tough), Some(&Health(250)));
+
+    // enums and their requirements of requirements work the same way
+    let boss = world.spawn(Rank::Boss).id();
+    assert_eq!(world.get::<Health>(boss), Some(&Health(100)));
+    assert_eq!(world.get::<Armor>(boss), Some(&Armor(0)));
+    println!("Required components inserted");
+
+    // components of other crates get their requirements through the marked use
+    let named = world.spawn(Name::new("Crate")).id();
+    assert_eq!(world.get::<Health>(named), Some(&Health(5)));
+    println!("Third party requirements registered");
+}
+
+#[plugin]
+mod enemies {
+    #[requires(Health = Health(5))]
+    use bevy::prelude::Name;
+
+    #[derive(Component, Debug, PartialEq)]
+    pub struct Health(pub u32);
+
+    #[derive(Component, Debug, Default, PartialEq)]
+    pub struct Armor(pub u32);
+
+    #[derive(Component, Debug, Default, PartialEq)]
+    pub enum Team {
+        #[default]
+        Neutral,
+        Hostile
+    }
+
+    #[requires(Transform, Team = Team::Hostile, Health = Health(100))]
+    #[derive(Component, Default)]
+    pub struct Enemy;
+
+    #[requires(Enemy, Armor)]
+    #[derive(Component)]
+    pub enum Rank {
+        Grunt,
+        Boss
+    }
+}
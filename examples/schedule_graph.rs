@@ -0,0 +1,58 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Physics));
+
+    // the graph is read from the schedules of the app, so the orderings can be checked without running it
+    let graph = app.plugin_schedule_graph(&Physics::MANIFEST);
+    println!("{}", graph.to_dot());
+    println!("{}", graph.to_mermaid());
+
+    let update = graph.schedule("Update").expect("Physics has no Update systems.");
+    assert_eq!(update.systems, ["apply_gravity", "apply_velocity", "detect_collisions", "log_collisions", "resolve_collisions"]);
+
+    // priorities are chained from highest to lowest
+    assert!(graph.has_edge("apply_gravity", "apply_velocity"));
+    assert!(graph.has_edge("apply_velocity", "resolve_collisions"));
+
+    // before and after are ordered through the set of the system they name
+    assert!(graph.has_edge("apply_velocity", "detect_collisions"));
+    assert!(graph.has_edge("log_collisions", "detect_collisions"));
+    assert!(!graph.has_edge("detect_collisions", "resolve_collisions"));
+}
+
+#[plugin]
+mod physics {
+    #[derive(Component)]
+    pub struct Velocity(pub Vec3);
+
+    #[update]
+    #[priority(HIGHEST)]
+    fn apply_gravity(mut velocities: Query<&mut Velocity>) {
+        for mut velocity in &mut velocities {
+            velocity.0.y -= 9.8;
+        }
+    }
+
+    #[update]
+    #[priority(NORMAL)]
+    fn apply_velocity(mut bodies: Query<(&mut Transform, &Velocity)>) {
+        for (mut transform, velocity) in &mut bodies {
+            transform.translation += velocity.0;
+        }
+    }
+
+    #[update]
+    #[priority(LOWEST)]
+    fn resolve_collisions() {}
+
+    #[update]
+    #[after(apply_velocity)]
+    fn detect_collisions() {}
+
+    #[update]
+    #[before(detect_collisions)]
+    fn log_collisions() {}
+}
@@ -0,0 +1,132 @@
+use bevy::{ecs::system::RunSystemOnce, prelude::*, state::{app::StatesPlugin, state::FreelyMutableState}};
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    // menu state is declared by hand, so it adds its own cleanup, a cleanup added before the plugin is not added twice
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, StatesPlugin, ScopeCleanupPlugin::<Level>::default(), LevelPlugin))
+        .init_state::<MenuState>()
+        .add_plugins(ScopeCleanupPlugin::<MenuState>::default());
+    app.update();
+
+    let world = app.world_mut();
+    let title = world.spawn(ScopeLocal(MenuState::Title)).id();
+    let options = world.spawn(ScopeLocal(MenuState::Options)).id();
+    let menus = world.spawn(ScopeLocalAny(vec![MenuState::Title, MenuState::Options])).id();
+    let first = world.spawn(ScopeLocal(Level::First)).id();
+    let child = world.spawn(ScopeLocal(MenuState::Title)).id();
+    world.entity_mut(title).add_child(child);
+
+    // entities are only despawned when their own state is exited
+    set_state(&mut app, MenuState::Options);
+    assert!(app.world().get_entity(title).is_err());
+    assert!(app.world().get_entity(child).is_err());
+    assert!(app.world().get_entity(options).is_ok());
+    assert!(app.world().get_entity(menus).is_ok());
+
+    // entities alive in any of several states survive moving between them
+    set_state(&mut app, MenuState::Title);
+    assert!(app.world().get_entity(options).is_err());
+    assert!(app.world().get_entity(menus).is_ok());
+    set_state(&mut app, MenuState::Closed);
+    assert!(app.world().get_entity(menus).is_err());
+
+    // states declared with `#[init_state]` are cleaned up by their plugin
+    assert!(app.world().get_entity(first).is_ok());
+    assert!(app.world().get_resource::<Checkpoints>().is_none());
+    assert_eq!(app.world().resource::<LastCheckpoint>().0, None);
+    set_state(&mut app, Level::Second);
+    assert!(app.world().get_entity(first).is_err());
+
+    // entities are cleaned up after the `OnExit` systems of their state ran
+    assert_eq!(app.world().resource::<SeenOnExit>().0, 1);
+    println!("Scoped entities cleaned up");
+
+    // scoped resources exist before the systems entering their state run
+    assert_eq!(app.world().resource::<Checkpoints>().0, 1);
+    assert_eq!(app.world().resource::<Entered>().0, 1);
+    assert_eq!(app.world().resource::<LastCheckpoint>().0, Some(1));
+
+    // global entities survive scene resets, even when their parent does not
+    let world = app.world_mut();
+    let camera = world.spawn(ScopeGlobal).id();
+    let level = world.spawn(ScopeLocal(Level::Second)).id();
+    let player = world.spawn(ScopeGlobal).id();
+    world.entity_mut(level).add_child(player);
+    world.run_system_once(|scoped: ScopedEntities| {
+        assert_eq!(scoped.global().count(), 2);
+        assert_eq!(scoped.local().count(), 1);
+    }).unwrap();
+
+    // the state is reset on the next state transition
+    world.commands().queue(SceneReset::new().with_state::<Level>());
+    app.update();
+    app.update();
+    assert!(app.world().get_entity(level).is_err());
+    assert!(app.world().get_entity(camera).is_ok());
+    assert!(app.world().get::<Parent>(player).is_none());
+    assert_eq!(app.world().resource::<State<Level>>().get(), &Level::First);
+    assert!(app.world().get_resource::<Checkpoints>().is_none());
+    println!("Global entities kept through scene reset");
+}
+
+fn record_checkpoint(In(checkpoint): In<u32>, mut last: ResMut<LastCheckpoint>) {
+    last.0 = Some(checkpoint);
+}
+
+fn set_state<S: FreelyMutableState>(app: &mut App, state: S) {
+    app.world_mut().resource_mut::<NextState<S>>().set(state);
+    app.update();
+}
+
+#[derive(States, Clone, Debug, Default, Hash, PartialEq, Eq)]
+pub enum MenuState {
+    #[default]
+    Title,
+    Options,
+    Closed
+}
+
+#[plugin]
+mod level_plugin {
+    #[init_state]
+    pub enum Level {
+        #[default]
+        First,
+        Second
+    }
+
+    #[init_resource(scope = Level::Second)]
+    #[derive(Resource, Default)]
+    pub struct Checkpoints(pub u32);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Entered(pub u32);
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct LastCheckpoint(pub Option<u32>);
+
+    // piped systems reading a scoped resource are gated along with the system they pipe into
+    #[update]
+    #[pipe(record_checkpoint)]
+    fn checkpoint(checkpoints: Res<Checkpoints>) -> u32 {
+        checkpoints.0
+    }
+
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct SeenOnExit(pub usize);
+
+    #[exit(Level::First)]
+    fn exit_first(scoped: Query<&ScopeLocal<Level>>, mut seen: ResMut<SeenOnExit>) {
+        seen.0 = scoped.iter().count();
+    }
+
+    #[enter(Level::Second)]
+    fn enter_second(mut checkpoints: ResMut<Checkpoints>, mut entered: ResMut<Entered>) {
+        checkpoints.0 += 1;
+        entered.0 += 1;
+    }
+}
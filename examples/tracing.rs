@@ -0,0 +1,73 @@
+use bevy::prelude::*;
+use mod_plugins::{macros::*, resources::*};
+
+fn main() {
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, Simulation, Rendering));
+    for _ in 0..3 {
+        app.update();
+    }
+
+    let timings = app.world().resource::<SystemTimings>();
+    for (name, timing) in timings.all() {
+        println!("{name}: {} runs, min {:?}, avg {:?}, max {:?}", timing.runs, timing.min, timing.avg(), timing.max);
+    }
+
+    // every system of the plugin is timed, even when it returns early
+    assert_eq!(timings.get("Simulation::step").map(|timing| timing.runs), Some(3));
+    assert_eq!(timings.get("Simulation::skip").map(|timing| timing.runs), Some(3));
+    assert_eq!(timings.get("Simulation::setup").map(|timing| timing.runs), Some(1));
+
+    // exclusive systems are timed through the world they take
+    assert_eq!(timings.get("Simulation::spawn_world").map(|timing| timing.runs), Some(1));
+    assert_eq!(timings.get("Simulation::count").map(|timing| timing.runs), Some(3));
+
+    // only marked systems are traced, and traced systems are only timed when asked
+    assert!(timings.get("Rendering::draw").is_none());
+    assert!(timings.get("Rendering::cull").is_none());
+}
+
+#[plugin(trace(timing))]
+mod simulation {
+    #[init_resource]
+    #[derive(Resource, Default)]
+    pub struct Steps(pub u32);
+
+    #[startup]
+    fn setup(mut steps: ResMut<Steps>) {
+        steps.0 = 0;
+    }
+
+    #[update]
+    fn step(mut steps: ResMut<Steps>) {
+        steps.0 += 1;
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+
+    #[startup]
+    fn spawn_world(world: &mut World) {
+        world.spawn_empty();
+    }
+
+    #[update]
+    fn count(world: &mut World) {
+        if world.resource::<Steps>().0 > 1 { return }
+        world.spawn_empty();
+    }
+
+    #[update]
+    fn skip() {
+        if true { return }
+        unreachable!();
+    }
+}
+
+#[plugin]
+mod rendering {
+    #[update]
+    #[trace]
+    fn draw() {}
+
+    #[update]
+    fn cull() {}
+}
@@ -0,0 +1,202 @@
+use convert_case::{Case, Casing};
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{parse::{Parse, ParseStream}, Attribute, Fields, FnArg, GenericArgument, Ident, ItemEnum, ItemFn, LitStr, Meta, PathArguments, Token, Type};
+
+/// The options that can follow the executable type, like `serde [= "tag"]`.
+#[derive(Default)]
+pub(crate) struct ExecutableOptions {
+    pub serde: Option<Option<LitStr>>
+}
+
+impl Parse for ExecutableOptions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        while !input.is_empty() {
+            let option = input.parse::<Ident>()?;
+            match option.to_string().as_str() {
+                "serde" => {
+                    options.serde = Some(if input.peek(Token![=]) {
+                        input.parse::<Token![=]>()?;
+                        Some(input.parse()?)
+                    } else { None });
+                },
+                _ => return Err(syn::Error::new(option.span(), "Unknown executable option, expected `serde`."))
+            }
+
+            if input.is_empty() { break }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(options)
+    }
+}
+
+impl ExecutableOptions {
+    /// Creates the `SerdeExecutable` implementation if the executable can be serialized, tagged by its type name unless a tag is given.
+    pub(crate) fn serde_impl(&self, ident: &Ident) -> TokenStream {
+        let Some(tag) = &self.serde else { return quote! {} };
+        let tag = tag.clone().unwrap_or_else(|| LitStr::new(ident.to_string().as_str(), ident.span()));
+        quote! {
+            impl mod_plugins::resources::SerdeExecutable for #ident {
+                const TAG: &'static str = #tag;
+            }
+        }
+    }
+}
+
+/// A parsed `#[executable(Type [, options])]` attribute on a function.
+/// The type may also be an enum variant like `Action::Spawn`, making the function the executable of that variant.
+pub(crate) struct ExecutableAttr {
+    pub path: syn::Path,
+    pub options: ExecutableOptions
+}
+
+impl Parse for ExecutableAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path = input.parse()?;
+        let options = if input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            input.parse()?
+        } else { ExecutableOptions::default() };
+        Ok(Self { path, options })
+    }
+}
+
+/// Get the type of a `#[executable(Type, serde)]` function, which its plugin registers to be decoded by its tag.
+pub(crate) fn serde_function(func: &ItemFn) -> Option<Ident> {
+    let attr = executable_attribute(&func.attrs)?;
+    let attr = attr.parse_args::<ExecutableAttr>().expect("Failed to parse executable attribute.");
+    attr.options.serde.and(attr.path.get_ident().cloned())
+}
+
+/// Get the type of a `#[executable(serde)]` enum, which its plugin registers to be decoded by its tag.
+pub(crate) fn serde_enum(item: &ItemEnum) -> Option<Ident> {
+    let attr = executable_attribute(&item.attrs)?;
+    let options = match &attr.meta {
+        Meta::List(list) => list.parse_args::<ExecutableOptions>().expect("Failed to parse executable attribute."),
+        _ => ExecutableOptions::default()
+    };
+    options.serde.map(|_| item.ident.clone())
+}
+
+/// Get the `#[executable]` attribute of an item, if it has one.
+pub(crate) fn executable_attribute(attrs: &[Attribute]) -> Option<&Attribute> {
+    attrs.iter().find(|attr| attr.path().is_ident("executable"))
+}
+
+/// Get the name of the module holding the variant markers of the given executable enum.
+fn variants_module(ident: &Ident) -> Ident {
+    Ident::new(format!("__{}_variants", ident.to_string().to_case(Case::Snake)).as_str(), Span::call_site())
+}
+
+/// Get the type wrapped by `In<T>` if it is the first argument of the function.
+pub(crate) fn input_type(func: &ItemFn) -> Option<Type> {
+    let Some(FnArg::Typed(arg)) = func.sig.inputs.first() else { return None };
+    let Type::Path(path) = &*arg.ty else { return None };
+    let segment = path.path.segments.last()?;
+    if segment.ident != "In" { return None }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+    match args.args.first()? {
+        GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None
+    }
+}
+
+/// Implements `Executable` for an enum by matching on the variant and running the executable of that variant with its fields as input.
+pub(crate) fn executable_enum(item: ItemEnum, options: ExecutableOptions) -> TokenStream {
+    let ident = &item.ident;
+    let vis = &item.vis;
+    let module = variants_module(ident);
+    let markers = item.variants.iter().map(|variant| &variant.ident).collect::<Vec<_>>();
+
+    // match each variant, passing its fields in order
+    let arms = item.variants.iter().map(|variant| {
+        let variant_ident = &variant.ident;
+        let fields = (0..variant.fields.len())
+            .map(|idx| Ident::new(format!("field{idx}").as_str(), Span::call_site()))
+            .collect::<Vec<_>>();
+        let pattern = match &variant.fields {
+            Fields::Unit => quote! { #ident::#variant_ident },
+            Fields::Unnamed(_) => quote! { #ident::#variant_ident(#(#fields),*) },
+            Fields::Named(named) => {
+                let names = named.named.iter().map(|field| field.ident.as_ref().unwrap());
+                quote! { #ident::#variant_ident { #(#names: #fields),* } }
+            }
+        };
+        let input = match fields.as_slice() {
+            [field] => quote! { #field },
+            fields => quote! { (#(#fields),*) }
+        };
+
+        quote! {
+            #pattern => <Self as mod_plugins::resources::ExecutableVariant<#module::#variant_ident, _>>::execute_variant(#input, world)
+        }
+    });
+
+    let serde_impl = options.serde_impl(ident);
+
+    quote! {
+        #item
+
+        /// Markers for each variant of the executable enum, implemented by the executable of that variant.
+        #[doc(hidden)]
+        #vis mod #module {
+            #(pub struct #markers;)*
+        }
+
+        impl mod_plugins::resources::Executable<()> for #ident {
+            fn execute(self: Box<Self>, world: &mut bevy::prelude::World) {
+                match *self {
+                    #(#arms),*
+                }
+            }
+        }
+
+        #serde_impl
+    }
+}
+
+/// Implements the executable of an enum variant given as `Enum::Variant`, running the function with the variants fields as input.
+pub(crate) fn executable_variant(attr: &ExecutableAttr, func: &ItemFn) -> TokenStream {
+    let name = &func.sig.ident;
+
+    // the marker is in the variants module next to the enum
+    let mut segments = attr.path.segments.iter().cloned().collect::<Vec<_>>();
+    let variant = segments.pop().expect("Expected enum variant path.");
+    let enum_ident = segments.pop().expect("Expected enum variant path like `Enum::Variant`.");
+    let leading_colon = attr.path.leading_colon;
+    let enum_path = syn::Path {
+        leading_colon,
+        segments: segments.iter().cloned().chain([enum_ident.clone()]).collect()
+    };
+    let marker = syn::Path {
+        leading_colon,
+        segments: segments.iter().cloned().chain([variants_module(&enum_ident.ident).into(), variant]).collect()
+    };
+
+    // variants without fields may be executed by systems without an input
+    let execute = match input_type(func) {
+        Some(input) => quote! {
+            impl mod_plugins::resources::ExecutableVariant<#marker, #input> for #enum_path {
+                fn execute_variant(input: #input, world: &mut bevy::prelude::World) {
+                    mod_plugins::resources::ExecutableSystems::run::<#marker, bevy::prelude::In<#input>, _, _>(world, #name, input)
+                }
+            }
+        },
+        None => quote! {
+            impl mod_plugins::resources::ExecutableVariant<#marker, ()> for #enum_path {
+                fn execute_variant(_input: (), world: &mut bevy::prelude::World) {
+                    mod_plugins::resources::ExecutableSystems::run::<#marker, (), _, _>(world, #name, ())
+                }
+            }
+        }
+    };
+
+    quote! {
+        #execute
+
+        #func
+    }
+}
@@ -1,15 +1,86 @@
 use syn::*;
 use quote::quote;
 
+use crate::requires::{register_required, RequiredComponent};
+
 #[derive(Clone, Debug, Default)]
 pub(crate) struct InitializationSystems {
     pub events: Vec<Ident>,
-    pub registered: Vec<Ident>,
+    pub registered: Vec<Type>,
+    pub reflected_states: Vec<Ident>,
     pub states_def: Vec<Ident>,
-    pub states_nodef: Vec<Expr>
+    pub states_nodef: Vec<Expr>,
+    pub scoped_states: Vec<Ident>,
+    pub required: Vec<(Type, RequiredComponent)>,
+    pub prefab_assets: Vec<Ident>,
+    pub scoped_resources: Vec<(Ident, Expr)>,
+    pub spawn_observers: Vec<(Type, Ident)>,
+    pub serde_executables: Vec<Ident>
+}
+
+/// Get the state given to `#[init_resource(scope = State::Variant)]`, resources without one live for the whole app.
+pub(crate) fn resource_scope(attr: &Attribute) -> Option<Expr> {
+    let Meta::List(list) = &attr.meta else { return None };
+    list.parse_args_with(|input: parse::ParseStream| {
+        let option = input.parse::<Ident>()?;
+        if option != "scope" { return Err(Error::new(option.span(), "Unknown resource option, expected `scope`.")) }
+        input.parse::<Token![=]>()?;
+        input.parse::<Expr>()
+    }).map(Some).expect("Failed to parse init_resource attribute, expected `scope = State::Variant`.")
+}
+
+/// Get the types given to `#[register(..)]`, which lists the instantiations to register of a generic type.
+/// Without a list, the marked type itself is registered.
+pub(crate) fn registered_types(attr: &Attribute, ident: &Ident) -> Vec<Type> {
+    match &attr.meta {
+        Meta::List(list) => list.parse_args_with(punctuated::Punctuated::<Type, Token![,]>::parse_terminated)
+            .expect("Failed to parse register attribute, expected a list of types.")
+            .into_iter()
+            .collect(),
+        _ => vec![syn::parse_quote! { #ident }]
+    }
+}
+
+/// Whether the attributes derive `Reflect`, as `#[plugin(register_all)]` registers every type that does.
+pub(crate) fn derives_reflect(attrs: &[Attribute]) -> bool {
+    attrs.iter()
+        .filter(|attr| attr.path().is_ident("derive"))
+        .filter_map(|attr| attr.parse_args_with(punctuated::Punctuated::<Path, Token![,]>::parse_terminated).ok())
+        .flatten()
+        .any(|path| path.segments.last().is_some_and(|segment| segment.ident == "Reflect"))
 }
 
 impl InitializationSystems {
+    /// Adds the initialization that can not be chained onto the `App`, as it comes from extension traits.
+    pub(crate) fn append_build(&self, builds: &mut proc_macro2::TokenStream) {
+        for (resource, state) in &self.scoped_resources {
+            builds.extend(quote! {
+                mod_plugins::resources::AppScopedResourceExt::init_scoped_resource::<#resource, _>(app, #state);
+            });
+        }
+
+        // the cleanup may already have been added by hand or by another plugin, and plugins can only be added once
+        for state in &self.scoped_states {
+            builds.extend(quote! {
+                if !app.is_plugin_added::<mod_plugins::resources::ScopeCleanupPlugin<#state>>() {
+                    app.add_plugins(mod_plugins::resources::ScopeCleanupPlugin::<#state>::default());
+                }
+            });
+        }
+
+        for prefab in &self.prefab_assets {
+            builds.extend(quote! {
+                mod_plugins::resources::AppPrefabAssetExt::register_prefab_asset::<#prefab>(app);
+            });
+        }
+
+        for executable in &self.serde_executables {
+            builds.extend(quote! {
+                mod_plugins::resources::AppSerdeExecutableExt::register_serde_executable::<#executable>(app);
+            });
+        }
+    }
+
     pub(crate) fn append(&self, app_ext: &mut proc_macro2::TokenStream) {
         for event in &self.events {
             app_ext.extend(quote! {
@@ -23,6 +94,12 @@ impl InitializationSystems {
             });
         }
 
+        for state in &self.reflected_states {
+            app_ext.extend(quote! {
+                .register_type_mutable_state::<#state>()
+            });
+        }
+
         for state in &self.states_def {
             app_ext.extend(quote! {
                 .init_state::<#state>()
@@ -34,5 +111,15 @@ impl InitializationSystems {
                 .insert_state(#state)
             });
         }
+
+        for (prefab, observer) in &self.spawn_observers {
+            app_ext.extend(quote! {
+                .add_observer(<#prefab>::#observer)
+            });
+        }
+
+        for (component, required) in &self.required {
+            app_ext.extend(register_required(component, required));
+        }
     }
 }
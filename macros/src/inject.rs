@@ -0,0 +1,119 @@
+use convert_case::{Case, Casing};
+use proc_macro2::Span;
+use quote::quote;
+use syn::{parse::{Parse, ParseStream}, Attribute, Expr, FnArg, Ident, ItemFn, Pat, Token, Type};
+
+/// A `[name:] Type [= value]` argument of an injection attribute like `#[res(Time)]` or `#[local(counter: u32 = 0)]`.
+pub(crate) struct InjectArg {
+    pub name: Option<Ident>,
+    pub ty: Type,
+    pub value: Option<Expr>
+}
+
+impl Parse for InjectArg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let name = input.parse()?;
+            input.parse::<Token![:]>()?;
+            Some(name)
+        } else { None };
+        let ty = input.parse()?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else { None };
+        Ok(Self { name, ty, value })
+    }
+}
+
+impl InjectArg {
+    /// Get the name of the argument, defaulting to the snake case name of its type.
+    fn name_or_default(&self, plural: bool) -> Ident {
+        if let Some(name) = &self.name { return name.clone() }
+
+        let Type::Path(path) = &self.ty else { panic!("Could not name injected argument, give it a name with `name: Type`.") };
+        let mut name = path.path.segments.last().unwrap().ident.to_string().to_case(Case::Snake);
+        if plural {
+            name.push_str(if name.ends_with('s') || name.ends_with('x') || name.ends_with("ch") || name.ends_with("sh") { "es" } else { "s" });
+        }
+        Ident::new(name.as_str(), Span::call_site())
+    }
+}
+
+/// Adds the system parameter requested by an injection attribute to the function.
+pub(crate) fn inject(item: &mut ItemFn, attr_name: &str, attr: &Attribute) {
+    // commands do not need a type
+    if attr_name == "commands" {
+        let name = match attr.meta.require_list() {
+            Ok(list) => syn::parse2(list.tokens.clone()).expect("Failed to parse commands name."),
+            Err(_) => Ident::new("commands", Span::call_site())
+        };
+        push_argument(item, syn::parse2(quote! { mut #name: Commands }).unwrap());
+        return;
+    }
+
+    let arg: InjectArg = attr.parse_args().expect("Failed to parse injected argument, expected `[name:] Type`.");
+    if arg.value.is_some() && attr_name != "local" {
+        panic!("Only locals can be given an initial value.");
+    }
+
+    let ty = &arg.ty;
+    match attr_name {
+        "res" => {
+            let name = arg.name_or_default(false);
+            push_argument(item, syn::parse2(quote! { #name: Res<#ty> }).unwrap());
+        },
+
+        "res_mut" => {
+            let name = arg.name_or_default(false);
+            push_argument(item, syn::parse2(quote! { mut #name: ResMut<#ty> }).unwrap());
+        },
+
+        "assets" => {
+            let name = arg.name_or_default(true);
+            push_argument(item, syn::parse2(quote! { mut #name: ResMut<Assets<#ty>> }).unwrap());
+        },
+
+        "local" => {
+            let name = arg.name.clone().expect("Locals must be named like `#[local(name: Type)]`.");
+            match &arg.value {
+                // without a value, use the types default
+                None => push_argument(item, syn::parse2(quote! { mut #name: Local<#ty> }).unwrap()),
+
+                // with a value, store an option that is filled with the value on the first run
+                Some(value) => {
+                    push_argument(item, syn::parse2(quote! { mut #name: Local<Option<#ty>> }).unwrap());
+                    let block = &item.block;
+                    item.block = syn::parse2(quote! {
+                        {
+                            let #name: &mut #ty = #name.get_or_insert_with(|| #value);
+                            #block
+                        }
+                    }).expect("Failed to unwrap local block.");
+                }
+            }
+        },
+
+        _ => unreachable!()
+    }
+}
+
+/// Adds an argument to the function, panicking if the function already has an argument with the same name.
+pub(crate) fn push_argument(item: &mut ItemFn, arg: FnArg) {
+    if let Some(name) = argument_name(&arg) {
+        if item.sig.inputs.iter().filter_map(argument_name).any(|existing| existing == name) {
+            panic!("Function {} already has an argument named {name}.", item.sig.ident);
+        }
+    }
+    item.sig.inputs.push(arg);
+}
+
+fn argument_name(arg: &FnArg) -> Option<Ident> {
+    match arg {
+        FnArg::Typed(pat) => match &*pat.pat {
+            Pat::Ident(ident) => Some(ident.ident.clone()),
+            _ => None
+        },
+        FnArg::Receiver(_) => None
+    }
+}
@@ -0,0 +1,137 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse::{Parse, ParseStream}, Block, Ident, Pat, Token};
+
+/// How the queries of an `#[on(...)]` attribute are iterated.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum IterationMode {
+    /// A plain `for` loop over the query.
+    Sequential,
+    /// `par_iter` or `par_iter_mut`, the body is run in a closure.
+    Parallel,
+    /// Every pair of query items using `iter_combinations`.
+    Pairs
+}
+
+/// A query to iterate and whether it should be iterated mutably.
+pub(crate) struct IterationTarget {
+    pub mutable: bool,
+    pub query: Ident
+}
+
+impl Parse for IterationTarget {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mutable = input.parse::<Option<Token![mut]>>()?.is_some();
+        let query = input.parse()?;
+        Ok(Self { mutable, query })
+    }
+}
+
+/// A parsed `#[on([par | pairs] [mut] query, ... [as pattern])]` attribute.
+pub(crate) struct OnAttr {
+    pub mode: IterationMode,
+    pub targets: Vec<IterationTarget>,
+    pub pattern: Option<Pat>
+}
+
+impl Parse for OnAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // get the iteration mode, only if it is followed by a query so queries can still be named `par` or `pairs`
+        let mut mode = IterationMode::Sequential;
+        if input.peek(Ident) && (input.peek2(Ident) || input.peek2(Token![mut])) {
+            let ident = input.parse::<Ident>()?;
+            mode = match ident.to_string().as_str() {
+                "par" => IterationMode::Parallel,
+                "pairs" => IterationMode::Pairs,
+                _ => return Err(syn::Error::new(ident.span(), "Unknown iteration mode, expected `par` or `pairs`."))
+            };
+        }
+
+        // get each query to iterate, seperated by commas
+        let mut targets = vec![input.parse::<IterationTarget>()?];
+        while input.peek(Token![,]) {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() { break }
+            targets.push(input.parse()?);
+        }
+
+        // get the pattern the items are bound to
+        let pattern = if input.peek(Token![as]) {
+            input.parse::<Token![as]>()?;
+            Some(Pat::parse_single(input)?)
+        } else { None };
+
+        if targets.len() > 1 && mode != IterationMode::Sequential {
+            return Err(input.error("Only sequential iteration can join several queries."));
+        }
+
+        Ok(Self { mode, targets, pattern })
+    }
+}
+
+impl OnAttr {
+    /// Wraps the given block so that it is run for each item of the queries.
+    pub(crate) fn wrap(&self, block: &Block) -> TokenStream {
+        if self.targets.len() > 1 { return self.wrap_joined(block) }
+
+        let IterationTarget { mutable, query } = &self.targets[0];
+        let pattern = match &self.pattern {
+            Some(pattern) => quote! { #pattern },
+            None if *mutable && self.mode != IterationMode::Pairs => quote! { mut #query },
+            None => quote! { #query }
+        };
+
+        match (self.mode, mutable) {
+            (IterationMode::Sequential, false) => quote! {
+                for #pattern in #query.iter() #block
+            },
+            (IterationMode::Sequential, true) => quote! {
+                for #pattern in #query.iter_mut() #block
+            },
+            (IterationMode::Parallel, false) => quote! {
+                #query.par_iter().for_each(|#pattern| #block);
+            },
+            (IterationMode::Parallel, true) => quote! {
+                #query.par_iter_mut().for_each(|#pattern| #block);
+            },
+            (IterationMode::Pairs, false) => quote! {
+                for #pattern in #query.iter_combinations::<2>() #block
+            },
+            (IterationMode::Pairs, true) => quote! {
+                let mut _combinations = #query.iter_combinations_mut::<2>();
+                while let Some(#pattern) = _combinations.fetch_next() #block
+            }
+        }
+    }
+
+    /// Iterates the entities of the first query, running the block for entities that match every query.
+    fn wrap_joined(&self, block: &Block) -> TokenStream {
+        let first = &self.targets[0].query;
+        let bindings = self.targets.iter().map(|IterationTarget { mutable, query }| {
+            if *mutable { quote! { mut #query } } else { quote! { #query } }
+        });
+        let gets = self.targets.iter().map(|IterationTarget { mutable, query }| {
+            if *mutable { quote! { #query.get_mut(_entity) } } else { quote! { #query.get(_entity) } }
+        });
+        let oks = (0..self.targets.len())
+            .map(|idx| Ident::new(format!("_item{idx}").as_str(), proc_macro2::Span::call_site()))
+            .collect::<Vec<_>>();
+        let pattern = match &self.pattern {
+            Some(pattern) => quote! { #pattern },
+            None => quote! { (#(#bindings),*) }
+        };
+
+        quote! {
+            #[allow(unused_mut)]
+            let mut #first = #first;
+            let _entities = #first.transmute_lens::<bevy::prelude::Entity>().query().iter().collect::<Vec<_>>();
+            for _entity in _entities {
+                let #pattern = match (#(#gets),*) {
+                    (#(Ok(#oks)),*) => (#(#oks),*),
+                    _ => continue
+                };
+                #block
+            }
+        }
+    }
+}
@@ -1,49 +1,75 @@
-use convert_case::Casing;
+use executable::{ExecutableAttr, ExecutableOptions};
 use initialization::InitializationSystems;
+use options::PluginOptions;
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use quote::quote;
-use syn::{parse_macro_input, Fields, FnArg, Ident, ItemFn, ItemMod, ItemStruct, Token, Type};
+use syn::{parse::Parser, parse_macro_input, Fields, FnArg, Ident, ItemEnum, ItemFn, ItemMod, ItemStruct, Type};
 use systems::SystemProcessor;
 
+mod executable;
 mod initialization;
+mod inject;
+mod iteration;
+mod options;
+mod prefab;
+mod query;
+mod requires;
 mod systems;
+mod trace;
 
 #[proc_macro_attribute]
 pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
     // unpack
     let input: ItemMod = syn::parse(input).unwrap();
+    let options = parse_macro_input!(attr as PluginOptions);
 
     // get structure name, if no structure name given, generate from the module name
-    let struct_name = if attr.is_empty() {
-        // return structure name where the modules name is converted from snake case to cammel case
-        let name = input.ident.to_string();
-        let struct_name = name.to_string()
-            .split("_")
-            .map(|token| {
-                let mut chars: Vec<char> = token.chars().collect();
-                chars[0] = chars[0].to_uppercase().nth(0).unwrap();
-                chars.into_iter().collect()
-            }).collect::<Vec<String>>().join("");
-        Ident::new(struct_name.as_str(), Span::call_site())
-    } else {
+    let struct_name = match options.name {
         // return the structure name given
-        syn::parse(attr).unwrap()
+        Some(name) => name,
+
+        // return structure name where the modules name is converted from snake case to cammel case
+        None => {
+            let name = input.ident.to_string();
+            let struct_name = name.to_string()
+                .split("_")
+                .map(|token| {
+                    let mut chars: Vec<char> = token.chars().collect();
+                    chars[0] = chars[0].to_uppercase().nth(0).unwrap();
+                    chars.into_iter().collect()
+                }).collect::<Vec<String>>().join("");
+            Ident::new(struct_name.as_str(), Span::call_site())
+        }
     };
 
     // setup some stuff for compute and output
     let mut output = proc_macro2::TokenStream::new();
     let mut init = InitializationSystems::default();
-    let mut systems = SystemProcessor::default();
+    let mut systems = SystemProcessor::new(&struct_name, options.trace);
     let mut fields = Vec::<syn::Field>::new();
     let mut default_resources = Vec::<syn::Ident>::new();
 
+    // prefabs observed with `#[on_spawn]` need a marker and variants need the fields of their base, which are only known once every item has been seen
+    let mut items = input.content.unwrap().1;
+    prefab::mark_spawned(&mut items);
+    prefab::flatten_variants(&mut items);
+
     // assemble initial output
-    for input in input.content.unwrap().1 {
+    for input in items {
         match input {
+            // executables are not systems of the plugin, but serialized ones are registered by it
+            syn::Item::Fn(item) if executable::executable_attribute(&item.attrs).is_some() => {
+                init.serde_executables.extend(executable::serde_function(&item));
+                output.extend(quote! { #item });
+            }
+
             syn::Item::Fn(item) => systems.process_item_fn(item),
 
             syn::Item::Struct(mut struct_item) => {
+                let mut required = Vec::new();
+                let mut registered = false;
+
                 // go through each attribute, choosing whether we should keep it or not (Rust how no idea what our custom attributes are and will cancel)
                 struct_item.attrs.retain(|attr| {
                     // attempt to get the attributes path as its identifier, otherwise, return keep
@@ -56,7 +82,10 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
                         match meta_name {
                             // make the resource initialize by its default in the App
                             "init_resource" => {
-                                default_resources.push(struct_item.ident.clone());
+                                match initialization::resource_scope(attr) {
+                                    Some(state) => init.scoped_resources.push((struct_item.ident.clone(), state)),
+                                    None => default_resources.push(struct_item.ident.clone())
+                                }
                                 false
                             },
 
@@ -66,20 +95,50 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
                             }
 
                             "register" => {
-                                init.registered.push(struct_item.ident.clone());
+                                init.registered.extend(initialization::registered_types(attr, &struct_item.ident));
+                                registered = true;
                                 false
                             }
 
+                            "requires" => {
+                                required.extend(requires::parse_requires(attr));
+                                false
+                            }
+
+                            // asset prefabs need their loader registered, the prefab attribute is kept to create them
+                            "prefab" => {
+                                let asset = prefab::PrefabAttr::from_meta(&attr.meta).is_ok_and(|prefab| prefab.asset);
+                                if asset { init.prefab_assets.push(struct_item.ident.clone()); }
+                                true
+                            }
+
                             _ => true
                         }
                     } else { true }
                 });
 
+                // generic types can only be registered through the instantiations listed with `#[register(..)]`
+                let reflected = initialization::derives_reflect(&struct_item.attrs) && struct_item.generics.params.is_empty();
+                if options.register_all && reflected && !registered {
+                    let ident = &struct_item.ident;
+                    init.registered.push(syn::parse_quote! { #ident });
+                }
+
+                // required components must come after the components derive
+                if !required.is_empty() {
+                    struct_item.attrs.push(requires::require_attribute(&required));
+                }
+
                 output.extend(quote! { #struct_item })
             },
 
             syn::Item::Enum(mut enum_item) => {
+                init.serde_executables.extend(executable::serde_enum(&enum_item));
                 let mut extras = proc_macro2::TokenStream::new();
+                let mut required = Vec::new();
+                let mut registered = false;
+                let mut state = false;
+                let reflected = initialization::derives_reflect(&enum_item.attrs) && enum_item.generics.params.is_empty();
 
                 // go through each attribute, choosing whether we should keep it or not (Rust how no idea what our custom attributes are and will cancel)
                 enum_item.attrs.retain(|attr| {
@@ -93,7 +152,10 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
                         match meta_name {
                             // make the resource initialize by its default in the App
                             "init_resource" => {
-                                default_resources.push(enum_item.ident.clone());
+                                match initialization::resource_scope(attr) {
+                                    Some(state) => init.scoped_resources.push((enum_item.ident.clone(), state)),
+                                    None => default_resources.push(enum_item.ident.clone())
+                                }
                                 false
                             },
 
@@ -110,37 +172,29 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
                                     init.states_def.push(enum_item.ident.clone());
                                 }
 
-                                // add derive macros
+                                // add derive macros, registered states are also reflected so that their current value can be inspected
                                 extras.extend(quote! {
                                     #[derive(States, Clone, Debug, Default, Hash, PartialEq, Eq, PartialOrd, Ord)]
                                 });
+                                if options.register_all && !reflected {
+                                    extras.extend(quote! { #[derive(Reflect)] });
+                                }
+                                state = true;
 
-                                // add system for each variant
-                                let ident = &enum_item.ident;
-                                enum_item.variants.iter().for_each(|variant| {
-                                    let variant = &variant.ident;
-                                    let func_name = Ident::new(format!("_remove_{}", variant.to_string()).to_case(convert_case::Case::Snake).as_str(), Span::call_site());
-                                    let enter_function = quote! {
-                                        #[enter(#ident::#variant)]
-                                        fn #func_name(
-                                            mut commands: bevy::prelude::Commands,
-                                            mut query: bevy::prelude::Query<(bevy::prelude::Entity, &mod_plugins::resources::ScopeLocal<#ident>)>
-                                        ) {
-                                            query.iter().for_each(|(entity, scope)| {
-                                                if (scope.0 != #ident::#variant) {
-                                                    commands.entity(entity).despawn_recursive();
-                                                }
-                                            });
-                                        }
-                                    };
-                                    systems.process_item_fn(syn::parse2(enter_function).expect("Could not compile enter remove state function."));
-                                });
+                                // despawn the entities scoped to each variant as it is exited
+                                init.scoped_states.push(enum_item.ident.clone());
 
                                 false
                             }
 
                             "register" => {
-                                init.registered.push(enum_item.ident.clone());
+                                init.registered.extend(initialization::registered_types(attr, &enum_item.ident));
+                                registered = true;
+                                false
+                            }
+
+                            "requires" => {
+                                required.extend(requires::parse_requires(attr));
                                 false
                             }
 
@@ -149,6 +203,19 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
                     } else { true }
                 });
 
+                // states are registered along with their `State` and `NextState` resources
+                if options.register_all && state && !registered {
+                    init.reflected_states.push(enum_item.ident.clone());
+                } else if options.register_all && reflected && !registered {
+                    let ident = &enum_item.ident;
+                    init.registered.push(syn::parse_quote! { #ident });
+                }
+
+                // required components must come after the components derive
+                if !required.is_empty() {
+                    enum_item.attrs.push(requires::require_attribute(&required));
+                }
+
                 output.extend(quote! { 
                     #extras 
                     #enum_item 
@@ -183,6 +250,30 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
 
                 if passthrough { output.extend(quote! { #type_item }) }
             }
+
+            syn::Item::Impl(mut impl_item) => {
+                // functions marked with `#[on_spawn]` are observers of the prefabs marker
+                for on_spawn in prefab::on_spawns(&mut impl_item) {
+                    init.spawn_observers.push((*impl_item.self_ty.clone(), on_spawn));
+                }
+
+                output.extend(quote! { #impl_item });
+            }
+
+            syn::Item::Use(mut use_item) => {
+                // third party components cannot be marked directly, so register their required components when the plugin is built
+                use_item.attrs.retain(|attr| {
+                    if attr.path().is_ident("requires") {
+                        let component = requires::use_tree_type(&use_item.tree);
+                        for required in requires::parse_requires(attr) {
+                            init.required.push((component.clone(), required));
+                        }
+                        false
+                    } else { true }
+                });
+
+                output.extend(quote! { #use_item });
+            }
             
             // by default, just add to the output
             _ => {
@@ -197,10 +288,12 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
     let mut impl_funcs = proc_macro2::TokenStream::new();
     let mut base_funcs = proc_macro2::TokenStream::new();
     init.append(&mut app_ext);
+    init.append_build(&mut builds);
 
     // apply systems
     systems.apply_build(&mut builds);
-    systems.apply_app_exts(&mut app_ext);
+    systems.apply_one_shots(&struct_name, &mut builds, &mut output);
+    systems.apply_app_exts(&mut app_ext, &init.scoped_resources.iter().map(|(resource, _)| resource.clone()).collect::<Vec<_>>());
     for impl_func in systems.impl_functions().iter() {
         impl_funcs.extend(quote! { #impl_func });
     }
@@ -211,6 +304,12 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
         app_ext.extend(quote! { .insert_resource(#default_res::default()) });
     }
 
+    // describe everything the plugin adds, so it can be inspected at runtime
+    let manifest = manifest(&struct_name, &init, &systems, &default_resources);
+    builds.extend(quote! {
+        mod_plugins::resources::AppPluginManifestExt::add_plugin_manifest(app, Self::MANIFEST);
+    });
+
     // compile after struct
     let after_struct = if fields.is_empty() { quote! { ; } } else {
         quote! {
@@ -232,6 +331,8 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
         }
 
         impl #struct_name {
+            pub const MANIFEST: mod_plugins::resources::PluginManifest = #manifest;
+
             #impl_funcs
         }
 
@@ -241,39 +342,93 @@ pub fn plugin(attr: TokenStream, input: TokenStream) -> TokenStream {
     output.into()
 }
 
+/// Creates the `PluginManifest` listing what the plugin adds to the `App`.
+fn manifest(name: &Ident, init: &InitializationSystems, systems: &SystemProcessor, default_resources: &[Ident]) -> proc_macro2::TokenStream {
+    let name = name.to_string();
+    let system_manifests = systems.system_manifests(&init.scoped_resources.iter().map(|(resource, _)| resource.clone()).collect::<Vec<_>>());
+    let mut observers = systems.observer_names();
+    observers.extend(init.spawn_observers.iter().map(|(prefab, observer)| format!("{}::{observer}", systems::token_string(quote! { #prefab }))));
+    let events = init.events.iter().map(|event| event.to_string());
+    let states = init.scoped_states.iter().map(|state| state.to_string());
+    let mut resources = default_resources.iter()
+        .chain(init.scoped_resources.iter().map(|(resource, _)| resource))
+        .map(|resource| resource.to_string())
+        .collect::<Vec<_>>();
+    resources.extend(systems.produced_resources());
+    let registered = init.registered.iter().map(|registered| systems::token_string(quote! { #registered }))
+        .chain(init.reflected_states.iter().chain(init.prefab_assets.iter()).map(|registered| registered.to_string()));
+
+    quote! {
+        mod_plugins::resources::PluginManifest {
+            name: #name,
+            module: module_path!(),
+            systems: &[#(#system_manifests),*],
+            observers: &[#(#observers),*],
+            events: &[#(#events),*],
+            states: &[#(#states),*],
+            resources: &[#(#resources),*],
+            registered: &[#(#registered),*]
+        }
+    }
+}
+
 #[proc_macro_attribute]
 pub fn executable(attr: TokenStream, input: TokenStream) -> TokenStream {
-    let ident = parse_macro_input!(attr as Ident);
+    // enums run the executable of their variant
+    if let Ok(item) = syn::parse::<ItemEnum>(input.clone()) {
+        let options = parse_macro_input!(attr as ExecutableOptions);
+        return TokenStream::from(executable::executable_enum(item, options));
+    }
+
+    let attr = parse_macro_input!(attr as ExecutableAttr);
     let mut func = parse_macro_input!(input as ItemFn);
+
+    // functions for an enum variant are given as `Enum::Variant`
+    if attr.path.segments.len() > 1 {
+        return TokenStream::from(executable::executable_variant(&attr, &func));
+    }
+
+    let ident = attr.path.get_ident().expect("Expected executable type.");
+    let serde_impl = attr.options.serde_impl(ident);
+
+    // executables that take `In<T>` as their first argument receive themselves as the system input
+    let takes_input = executable::input_type(&func).is_some();
     let sig = &mut func.sig;
     let name = &sig.ident;
 
-    // add current arg
-    let current = quote! { current: Res<mod_plugins::resources::Current<Box<#ident>>> };
-    let current = TokenStream::from(current);
-    sig.inputs.push(parse_macro_input!(current as FnArg));
-
     // get return type with hacky workaround for ()
     let empty = Box::new(Type::Verbatim(quote! { () }));
     let ret = match &sig.output {
         syn::ReturnType::Default => &empty,
         syn::ReturnType::Type(_, b) => b,
+    }.clone();
+
+    // otherwise, add current arg so the executable is read from a resource
+    let execute = if takes_input {
+        quote! {
+            mod_plugins::resources::ExecutableSystems::run::<Self, bevy::prelude::In<Self>, _, _>(world, #name, *self)
+        }
+    } else {
+        let current = quote! { current: Res<mod_plugins::resources::Current<Box<#ident>>> };
+        let current = TokenStream::from(current);
+        sig.inputs.push(parse_macro_input!(current as FnArg));
+
+        quote! {
+            mod_plugins::resources::WorldCurrentExt::with_current(world, self, |world| {
+                mod_plugins::resources::ExecutableSystems::run::<Self, (), _, _>(world, #name, ())
+            })
+        }
     };
 
     TokenStream::from(quote! {
         impl mod_plugins::resources::Executable<#ret> for #ident {
             fn execute(self: Box<Self>, world: &mut bevy::prelude::World) -> #ret {
-                let mut system = bevy::prelude::IntoSystem::into_system(#name);
-                world.insert_resource(mod_plugins::resources::Current::new(self));
-                system.initialize(world);
-                let response = system.run((), world);
-                system.apply_deferred(world);
-                world.remove_resource::<mod_plugins::resources::Current<Self>>();
-        
-                response
+                #execute
             }
         }
 
+        #serde_impl
+
         #func
     })
 }
@@ -282,55 +437,53 @@ pub fn executable(attr: TokenStream, input: TokenStream) -> TokenStream {
 pub fn prefab(attr: TokenStream, input: TokenStream) -> TokenStream {
     // parse inputs
     let mut input = parse_macro_input!(input as ItemStruct);
-    let tokens = systems::tokens_to_strings(attr.into());
-
-    // setup scope if given and using named fields
-    if let Fields::Named(fields) = &mut input.fields {
-        // get index of scope attribute and check if it was given
-        let scope_idx = tokens.iter()
-            .enumerate()
-            .find(|a| a.1 == "scope")
-            .map(|a| a.0);
-        if let Some(scope_idx) = scope_idx {
-            // get the scope token and add
-            if let Some(scope) = tokens.iter().nth(scope_idx + 1) {
-                // add field based on scope given
-                match scope.as_str() {
-                    // add global field
-                    "global" => {
-                        fields.named.push(syn::Field {
-                            attrs: vec![],
-                            vis: syn::Visibility::Public(<Token![pub]>::default()),
-                            mutability: syn::FieldMutability::None,
-                            ident: Some(Ident::new("scope", Span::call_site())),
-                            colon_token: Some(<Token![:]>::default()),
-                            ty: syn::parse2(quote! { mod_plugins::resources::ScopeGlobal }).unwrap()
-                        });
-                    },
-
-                    // add local field
-                    "local" => {
-                        let state = Ident::new(tokens[scope_idx + 2].as_str(), Span::call_site());
-                        fields.named.push(syn::Field {
-                            attrs: vec![],
-                            vis: syn::Visibility::Public(<Token![pub]>::default()),
-                            mutability: syn::FieldMutability::None,
-                            ident: Some(Ident::new("scope", Span::call_site())),
-                            colon_token: Some(<Token![:]>::default()),
-                            ty: syn::parse2(quote! { mod_plugins::resources::ScopeLocal<#state> }).unwrap()
-                        })
-                    },
-
-                    // panic if unknown scope
-                    _ => panic!("Unknown scope {scope:?}")
-                }
-            }
+    let attr = parse_macro_input!(attr as prefab::PrefabAttr);
+
+    // variants are flattened by their plugin, which can see the prefab they extend
+    if attr.extends.is_some() {
+        panic!("Prefab {} must be declared in the same plugin as the prefab it extends.", input.ident);
+    }
+
+    // bundles can not be derived for unit structs, so unit prefabs become empty tuple prefabs
+    if let Fields::Unit = input.fields {
+        input.fields = Fields::Unnamed(syn::parse_quote! { () });
+    }
+
+    // prefabs with spawn observers hold a marker so their spawns can be observed
+    let marker = if attr.marker { prefab::marker(&mut input, attr.asset) } else { quote! {} };
+
+    // add the scope as the last field
+    if let Some(scope) = &attr.scope {
+        let ty = scope.field_type();
+        match &mut input.fields {
+            Fields::Named(fields) => fields.named.push(syn::Field::parse_named.parse2(quote! { #[prefab_scope] pub scope: #ty }).unwrap()),
+            Fields::Unnamed(fields) => fields.unnamed.push(syn::Field::parse_unnamed.parse2(quote! { #[prefab_scope] pub #ty }).unwrap()),
+            Fields::Unit => unreachable!()
         }
     }
 
+    let asset = attr.asset;
+
+    // create constructors
+    let constructors = prefab::constructors(&mut input, asset);
+
+    // create the spawn function, prefabs with children spawn a hierarchy instead of being a bundle
+    let scoped = matches!(input.fields, Fields::Named(_)) && attr.scope.is_some();
+    let (spawn, has_children) = prefab::spawn(&mut input, scoped);
+    if asset && has_children { panic!("Asset prefabs can not have children.") }
+
+    let derive = if has_children { quote! {} } else { quote! { #[derive(Bundle)] } };
+    let derive = if asset { quote! { #derive #[derive(Asset, Reflect)] } } else { derive };
+
     // compile and return result
     TokenStream::from(quote! {
-        #[derive(Bundle, Default)]
+        #derive
         #input
+
+        #marker
+
+        #constructors
+
+        #spawn
     })
 }
@@ -0,0 +1,38 @@
+use syn::{parse::{Parse, ParseStream}, Ident, Token};
+
+use crate::trace::TraceOptions;
+
+/// The arguments of `#[plugin(..)]`, the name of the plugin struct followed by any options, like `Name, trace(timing), register_all`.
+#[derive(Default)]
+pub(crate) struct PluginOptions {
+    pub name: Option<Ident>,
+    pub trace: Option<TraceOptions>,
+    pub register_all: bool
+}
+
+impl Parse for PluginOptions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        while !input.is_empty() {
+            let option = input.parse::<Ident>()?;
+            match option.to_string().as_str() {
+                "trace" => {
+                    options.trace = Some(if input.peek(syn::token::Paren) {
+                        let content;
+                        syn::parenthesized!(content in input);
+                        content.parse()?
+                    } else { TraceOptions::default() });
+                },
+                "register_all" => options.register_all = true,
+                _ if options.name.is_none() && options.trace.is_none() && !options.register_all => options.name = Some(option),
+                _ => return Err(syn::Error::new(option.span(), "Unknown plugin option, expected `trace` or `register_all`."))
+            }
+
+            if input.is_empty() { break }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(options)
+    }
+}
@@ -0,0 +1,525 @@
+use std::collections::HashMap;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::quote;
+use convert_case::{Case, Casing};
+use syn::{braced, parenthesized, parse::{Parse, ParseStream, Parser}, parse_quote, punctuated::Punctuated, Attribute, Expr, Field, Fields, Ident, ImplItem, Index, Item, ItemImpl, ItemStruct, LitStr, Member, Meta, Token, Type};
+
+/// The scope given to a prefab, adding a `scope` field holding its scope components.
+#[derive(Clone)]
+pub(crate) enum PrefabScope {
+    Global,
+    /// Local to one or more states, despawned when any of them changes.
+    Local(Vec<Type>)
+}
+
+impl PrefabScope {
+    /// Get the type of the `scope` field, a tuple of local scopes when there are several states.
+    pub(crate) fn field_type(&self) -> Type {
+        match self {
+            Self::Global => parse_quote! { mod_plugins::resources::ScopeGlobal },
+            Self::Local(states) => match states.as_slice() {
+                [state] => parse_quote! { mod_plugins::resources::ScopeLocal<#state> },
+                states => parse_quote! { (#(mod_plugins::resources::ScopeLocal<#states>),*) }
+            }
+        }
+    }
+}
+
+/// Field values replacing the defaults of a nested prefab, like `{ health: Health(50), speed: Speed(2.0) }`.
+#[derive(Clone, Default)]
+pub(crate) struct Overrides(Vec<(Ident, Expr)>);
+
+impl Parse for Overrides {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let overrides = Punctuated::<(Ident, Expr), Token![,]>::parse_terminated_with(input, |input| {
+            let name = input.parse()?;
+            input.parse::<Token![:]>()?;
+            Ok((name, input.parse()?))
+        })?;
+        Ok(Self(overrides.into_iter().collect()))
+    }
+}
+
+impl Overrides {
+    /// Get the default value of the nested prefab with each override applied through its `with_*` builder methods.
+    fn apply(&self, ty: &Type) -> Expr {
+        let withs = self.0.iter().map(|(name, value)| {
+            let with = Ident::new(format!("with_{name}").as_str(), name.span());
+            quote! { .#with(#value) }
+        });
+        parse_quote! { <#ty as Default>::default() #(#withs)* }
+    }
+}
+
+/// A parsed `#[prefab(...)]` attribute, like `#[prefab(scope = local(game::AppState), extends = Base { health: Health(5) }, asset, marker)]`.
+/// The older `scope global` and `scope local State` forms are still accepted.
+#[derive(Clone, Default)]
+pub(crate) struct PrefabAttr {
+    pub scope: Option<PrefabScope>,
+    pub extends: Option<(Type, Overrides)>,
+    pub asset: bool,
+    /// Whether the prefab holds a marker, added by the plugin for prefabs with `#[on_spawn]` observers.
+    pub marker: bool
+}
+
+impl Parse for PrefabAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut attr = Self::default();
+
+        while !input.is_empty() {
+            let option = input.parse::<Ident>()?;
+            match option.to_string().as_str() {
+                "scope" => {
+                    let assigned = input.parse::<Option<Token![=]>>()?.is_some();
+                    let kind = input.parse::<Ident>()?;
+                    attr.scope = Some(match kind.to_string().as_str() {
+                        "global" => PrefabScope::Global,
+                        "local" if assigned => {
+                            let content;
+                            parenthesized!(content in input);
+                            let states = Punctuated::<Type, Token![,]>::parse_terminated(&content)?;
+                            if states.is_empty() { return Err(syn::Error::new(kind.span(), "Expected at least one state, like `local(AppState)`.")) }
+                            PrefabScope::Local(states.into_iter().collect())
+                        },
+                        "local" => PrefabScope::Local(vec![input.parse()?]),
+                        _ => return Err(syn::Error::new(kind.span(), "Unknown scope, expected `global` or `local(State, ..)`."))
+                    });
+                },
+                "extends" => {
+                    input.parse::<Token![=]>()?;
+                    let base = input.parse()?;
+                    let overrides = if input.peek(syn::token::Brace) {
+                        let content;
+                        braced!(content in input);
+                        content.parse()?
+                    } else { Overrides::default() };
+                    attr.extends = Some((base, overrides));
+                },
+                "asset" => attr.asset = true,
+                "marker" => attr.marker = true,
+                _ => return Err(syn::Error::new(option.span(), "Unknown prefab option, expected `scope`, `extends`, `asset` or `marker`."))
+            }
+
+            if input.is_empty() { break }
+            input.parse::<Option<Token![,]>>()?;
+        }
+
+        Ok(attr)
+    }
+}
+
+impl PrefabAttr {
+    /// Parses the options of a `#[prefab]` attribute found on a struct, which may have none.
+    pub(crate) fn from_meta(meta: &Meta) -> syn::Result<Self> {
+        match meta {
+            Meta::List(list) => list.parse_args(),
+            _ => Ok(Self::default())
+        }
+    }
+
+    /// Get the attribute with these options, which no longer extends a base once its fields have been flattened.
+    fn attribute(&self) -> Attribute {
+        let mut options = Vec::new();
+        match &self.scope {
+            Some(PrefabScope::Global) => options.push(quote! { scope = global }),
+            Some(PrefabScope::Local(states)) => options.push(quote! { scope = local(#(#states),*) }),
+            None => {}
+        }
+        if self.asset { options.push(quote! { asset }) }
+        if self.marker { options.push(quote! { marker }) }
+        parse_quote! { #[prefab(#(#options),*)] }
+    }
+}
+
+/// Flattens the fields of the prefab each variant extends into the variant, placing them before its own fields.
+/// Overridden fields of the base are defaulted to their override, and a variant of a base with spawn observers also holds the bases marker.
+/// Bases may themselves be variants, but must be declared in the same plugin.
+pub(crate) fn flatten_variants(items: &mut [Item]) {
+    let prefabs = items.iter()
+        .filter_map(|item| match item {
+            Item::Struct(item) => item.attrs.iter()
+                .find(|attr| attr.path().is_ident("prefab"))
+                .map(|attr| (item.ident.clone(), (item.clone(), PrefabAttr::from_meta(&attr.meta).expect("Failed to parse prefab attribute.")))),
+            _ => None
+        })
+        .collect::<HashMap<_, _>>();
+
+    for item in items {
+        let Item::Struct(item) = item else { continue };
+        let Some((_, attr)) = prefabs.get(&item.ident) else { continue };
+        if attr.extends.is_none() { continue }
+
+        let fields = flattened_fields(&item.ident, &prefabs, 0);
+        item.fields = Fields::Named(parse_quote! { { #(#fields),* } });
+        item.semi_token = None;
+
+        let attr = PrefabAttr { extends: None, ..attr.clone() };
+        let index = item.attrs.iter().position(|attr| attr.path().is_ident("prefab")).unwrap();
+        item.attrs[index] = attr.attribute();
+    }
+}
+
+/// Get the fields of the prefab with the fields of the bases it extends before them.
+fn flattened_fields(ident: &Ident, prefabs: &HashMap<Ident, (ItemStruct, PrefabAttr)>, depth: usize) -> Vec<Field> {
+    if depth > prefabs.len() { panic!("Prefab {ident} extends itself.") }
+    let (item, attr) = &prefabs[ident];
+    let own = match &item.fields {
+        Fields::Named(fields) => fields.named.iter().cloned().collect::<Vec<_>>(),
+        Fields::Unit => Vec::new(),
+        Fields::Unnamed(_) => panic!("Tuple prefab {ident} can not extend or be extended by another prefab.")
+    };
+    let Some((base, overrides)) = &attr.extends else { return own };
+
+    let base = match base {
+        Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.clone()),
+        _ => None
+    }.filter(|base| prefabs.contains_key(base))
+        .unwrap_or_else(|| panic!("Prefab {ident} must be declared in the same plugin as the prefab it extends."));
+
+    // the base keeps its defaults unless they are overridden
+    let mut fields = flattened_fields(&base, prefabs, depth + 1);
+    for (name, value) in &overrides.0 {
+        let field = fields.iter_mut()
+            .find(|field| field.ident.as_ref() == Some(name))
+            .unwrap_or_else(|| panic!("Prefab {base} has no field {name} to override."));
+        field.attrs.retain(|attr| !attr.path().is_ident("default"));
+        field.attrs.push(parse_quote! { #[default(#value)] });
+    }
+    if prefabs[&base].1.marker {
+        fields.push(marker_field(&base, true));
+    }
+
+    for field in own {
+        if fields.iter().any(|base_field| base_field.ident == field.ident) {
+            panic!("Prefab {ident} can not redeclare the field {} of {base}, override it instead.", field.ident.as_ref().unwrap());
+        }
+        fields.push(field);
+    }
+    fields
+}
+
+/// How a prefab field is filled when the prefab is created.
+enum FieldValue {
+    /// Filled by the types `Default` implementation, only used for the scope and marker added by the macro.
+    Default,
+    /// Filled by the expression given with `#[default(...)]`.
+    Expr(Expr),
+    /// Has no default, so it must be given to the `new` constructor.
+    Required
+}
+
+/// A field of a prefab, named by its index for tuple prefabs.
+struct PrefabField {
+    member: Member,
+    /// The argument of the `new` constructor when the field is required.
+    arg: Ident,
+    /// The `with_*` builder method of the field, the marker has none.
+    builder: Option<Ident>,
+    ty: Type,
+    value: FieldValue
+}
+
+/// Removes the `#[default(...)]` and `#[overrides(...)]` field attributes from the prefab, creating its `Default` implementation,
+/// a `new` constructor taking every field without a default and a `with_*` builder method for every field.
+/// Fields of tuple prefabs are given to `new` in order and replaced with `with_0`, `with_1`, ... except for the scope, which is replaced with `with_scope`.
+/// Asset prefabs also get `#[reflect(default)]` on every field that is not required, so it may be left out of the prefabs file.
+pub(crate) fn constructors(input: &mut ItemStruct, asset: bool) -> TokenStream {
+    let snake = input.ident.to_string().to_case(Case::Snake);
+
+    // get how each field is filled, removing our attributes so the bundle derive does not see them
+    let fields = input.fields.iter_mut().enumerate().map(|(index, field)| {
+        let (member, arg, name) = match &field.ident {
+            Some(ident) => (Member::Named(ident.clone()), ident.clone(), ident.to_string()),
+            None => (Member::Unnamed(Index::from(index)), Ident::new(format!("field_{index}").as_str(), Span::call_site()), index.to_string())
+        };
+        let mut value = FieldValue::Required;
+        let mut overrides = Overrides::default();
+        let mut scope = false;
+        let mut marker = false;
+        field.attrs.retain(|attr| {
+            if attr.path().is_ident("overrides") {
+                overrides = attr.parse_args().expect("Failed to parse nested prefab overrides, expected `field: value, ..`.");
+                false
+            } else if attr.path().is_ident("default") {
+                value = FieldValue::Expr(attr.parse_args().expect("Failed to parse prefab field default, expected an expression."));
+                false
+            } else if attr.path().is_ident("required") {
+                panic!("Prefab fields without `#[default(...)]` are already required, remove `#[required]` from {name}.");
+            } else if attr.path().is_ident("prefab_scope") {
+                scope = true;
+                false
+            } else if attr.path().is_ident("prefab_marker") {
+                marker = true;
+                false
+            } else { true }
+        });
+
+        // nested prefabs are defaulted with their overrides applied
+        if !overrides.0.is_empty() {
+            value = FieldValue::Expr(overrides.apply(&field.ty));
+        }
+
+        // the scope and marker are added by the macro, so they are filled by their own defaults
+        if (scope || marker) && matches!(value, FieldValue::Required) {
+            value = FieldValue::Default;
+        }
+
+        let builder = match (scope, marker) {
+            (_, true) => None,
+            (true, _) => Some(Ident::new("with_scope", Span::call_site())),
+            _ => Some(Ident::new(format!("with_{name}").as_str(), Span::call_site()))
+        };
+        PrefabField { member, arg, builder, ty: field.ty.clone(), value }
+    }).collect::<Vec<_>>();
+
+    // reflection can only default fields through a function, so one is created for each default expression
+    let mut default_fns = Vec::new();
+    if asset {
+        for (field, prefab_field) in input.fields.iter_mut().zip(&fields) {
+            match &prefab_field.value {
+                FieldValue::Default => field.attrs.push(parse_quote! { #[reflect(default)] }),
+                FieldValue::Expr(expr) => {
+                    let member = &prefab_field.member;
+                    let func = Ident::new(format!("__{snake}_default_{}", quote! { #member }).as_str(), Span::call_site());
+                    let path = LitStr::new(func.to_string().as_str(), Span::call_site());
+                    let ty = &prefab_field.ty;
+                    field.attrs.push(parse_quote! { #[reflect(default = #path)] });
+                    default_fns.push(quote! {
+                        #[doc(hidden)]
+                        fn #func() -> #ty { #expr }
+                    });
+                },
+                FieldValue::Required => {}
+            }
+        }
+    }
+
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // fill each field by its value, using the argument of the field for required fields
+    let inits = fields.iter().map(|PrefabField { member, arg, value, .. }| match value {
+        FieldValue::Default => quote! { #member: Default::default() },
+        FieldValue::Expr(expr) => quote! { #member: #expr },
+        FieldValue::Required => quote! { #member: #arg }
+    }).collect::<Vec<_>>();
+
+    // prefabs can only be defaulted when nothing is required
+    let required = fields.iter()
+        .filter(|field| matches!(field.value, FieldValue::Required))
+        .map(|PrefabField { arg, ty, .. }| quote! { #arg: #ty })
+        .collect::<Vec<_>>();
+    let default_impl = if required.is_empty() {
+        quote! {
+            impl #impl_generics Default for #ident #ty_generics #where_clause {
+                fn default() -> Self {
+                    Self { #(#inits),* }
+                }
+            }
+        }
+    } else { quote! {} };
+
+    let withs = fields.iter().filter_map(|PrefabField { member, builder, ty, .. }| builder.as_ref().map(|builder| quote! {
+        /// Replaces the value of this field.
+        #vis fn #builder(mut self, value: #ty) -> Self {
+            self.#member = value;
+            self
+        }
+    }));
+
+    quote! {
+        #default_impl
+
+        #(#default_fns)*
+
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Creates the prefab from its required fields, filling every other field with its default.
+            #vis fn new(#(#required),*) -> Self {
+                Self { #(#inits),* }
+            }
+
+            #(#withs)*
+        }
+    }
+}
+
+/// How a `#[child]` field of a prefab holds its child prefabs.
+enum ChildField {
+    Single,
+    Option,
+    Vec
+}
+
+impl ChildField {
+    fn from_type(ty: &Type) -> Self {
+        let Type::Path(path) = ty else { return Self::Single };
+        match path.path.segments.last().map(|segment| segment.ident.to_string()).as_deref() {
+            Some("Option") => Self::Option,
+            Some("Vec") => Self::Vec,
+            _ => Self::Single
+        }
+    }
+}
+
+/// Removes the `#[child]` field attributes from the prefab and creates its `spawn` function, which spawns the components of the prefab
+/// and then each child prefab as its children.  The scope of the prefab is given to the children that do not have their own.
+/// Returns whether the prefab has children, as it can then no longer be a `Bundle`.
+pub(crate) fn spawn(input: &mut ItemStruct, scoped: bool) -> (TokenStream, bool) {
+    let ident = &input.ident;
+    let vis = &input.vis;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    // split the fields into the components of the root and the children
+    let mut components = Vec::new();
+    let mut children = Vec::new();
+    if let Fields::Named(fields) = &mut input.fields {
+        for field in fields.named.iter_mut() {
+            let len = field.attrs.len();
+            field.attrs.retain(|attr| !attr.path().is_ident("child"));
+            let name = field.ident.clone().unwrap();
+            if field.attrs.len() == len { components.push(name) } else { children.push((name, ChildField::from_type(&field.ty))) }
+        }
+    }
+
+    if children.is_empty() {
+        return (quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                /// Spawns the prefab, returning its entity.
+                #vis fn spawn(self, commands: &mut bevy::prelude::Commands) -> bevy::prelude::Entity {
+                    commands.spawn(self).id()
+                }
+            }
+        }, false);
+    }
+
+    // bundles are only implemented for tuples of up to 15 elements, so the components are nested in chunks
+    let chunks = components.chunks(15).map(|chunk| quote! { (#(#chunk,)*) });
+    let names = components.iter().chain(children.iter().map(|(name, _)| name));
+
+    let spawn_children = children.iter().map(|(name, field)| match field {
+        ChildField::Single => quote! { __children.push(#name.spawn(commands)); },
+        ChildField::Option => quote! { __children.extend(#name.map(|child| child.spawn(commands))); },
+        ChildField::Vec => quote! { __children.extend(#name.into_iter().map(|child| child.spawn(commands))); }
+    });
+
+    let (copy_scope, propagate_scope) = if scoped {
+        (
+            quote! { let __scope = scope.clone(); },
+            quote! {
+                for child in &__children {
+                    commands.entity(*child).queue(mod_plugins::resources::PropagateScope(__scope.clone()));
+                }
+            }
+        )
+    } else { (quote! {}, quote! {}) };
+
+    (quote! {
+        impl #impl_generics #ident #ty_generics #where_clause {
+            /// Spawns the prefab with its child prefabs as its children, returning the root entity.
+            #vis fn spawn(self, commands: &mut bevy::prelude::Commands) -> bevy::prelude::Entity {
+                let Self { #(#names),* } = self;
+                #copy_scope
+                let __entity = commands.spawn((#(#chunks,)*)).id();
+
+                let mut __children = Vec::new();
+                #(#spawn_children)*
+                bevy::prelude::BuildChildren::add_children(&mut commands.entity(__entity), &__children);
+                #propagate_scope
+
+                __entity
+            }
+        }
+    }, true)
+}
+
+/// Get the name of the marker component added to every entity spawned from the prefab.
+pub(crate) fn marker_ident(ident: &Ident) -> Ident {
+    Ident::new(format!("{ident}Marker").as_str(), ident.span())
+}
+
+/// Get the hidden field holding the marker of the prefab, named after the prefab so the markers of several prefabs can not collide.
+pub(crate) fn marker_field(ident: &Ident, named: bool) -> Field {
+    let marker = marker_ident(ident);
+    if named {
+        let name = Ident::new(format!("__{}_marker", ident.to_string().to_case(Case::Snake)).as_str(), ident.span());
+        Field::parse_named.parse2(quote! { #[prefab_marker] #[doc(hidden)] pub #name: #marker }).unwrap()
+    } else {
+        Field::parse_unnamed.parse2(quote! { #[prefab_marker] #[doc(hidden)] pub #marker }).unwrap()
+    }
+}
+
+/// Adds a hidden field holding the prefabs marker component and creates the marker, so observers can run when the prefab is spawned.
+pub(crate) fn marker(input: &mut ItemStruct, asset: bool) -> TokenStream {
+    let vis = &input.vis;
+    let marker = marker_ident(&input.ident);
+    let field = marker_field(&input.ident, matches!(input.fields, Fields::Named(_)));
+    match &mut input.fields {
+        Fields::Named(fields) => fields.named.push(field),
+        Fields::Unnamed(fields) => fields.unnamed.push(field),
+        Fields::Unit => panic!("Unit prefabs should have become tuple prefabs.")
+    }
+
+    let reflect = if asset { quote! { #[derive(Reflect)] #[reflect(Component, Default)] } } else { quote! {} };
+    let doc = format!("Added to every entity spawned from a `{}`, used to observe when it is spawned.", input.ident);
+    quote! {
+        #[doc = #doc]
+        #[derive(Component, Clone, Copy, Debug, Default)]
+        #reflect
+        #vis struct #marker;
+    }
+}
+
+/// Adds the `marker` option to the prefabs in the items that have `#[on_spawn]` observers, as only those need a marker.
+pub(crate) fn mark_spawned(items: &mut [Item]) {
+    let spawned = items.iter()
+        .filter_map(|item| match item {
+            Item::Impl(item) => Some(item),
+            _ => None
+        })
+        .filter(|item| item.items.iter().any(|item| matches!(item, ImplItem::Fn(func) if func.attrs.iter().any(|attr| attr.path().is_ident("on_spawn")))))
+        .filter_map(|item| match &*item.self_ty {
+            Type::Path(path) => path.path.segments.last().map(|segment| segment.ident.clone()),
+            _ => None
+        })
+        .collect::<Vec<_>>();
+
+    for item in items {
+        let Item::Struct(item) = item else { continue };
+        if !spawned.contains(&item.ident) { continue }
+        let Some(attr) = item.attrs.iter_mut().find(|attr| attr.path().is_ident("prefab")) else { continue };
+        *attr = match &attr.meta {
+            Meta::List(list) => {
+                // the options may be empty or already end with a comma, like `#[prefab()]` or `#[prefab(asset,)]`
+                let tokens = &list.tokens;
+                let separated = tokens.clone().into_iter().last().is_none_or(|token| matches!(token, TokenTree::Punct(punct) if punct.as_char() == ','));
+                let comma = if separated { quote! {} } else { quote! { , } };
+                parse_quote! { #[prefab(#tokens #comma marker)] }
+            },
+            _ => parse_quote! { #[prefab(marker)] }
+        };
+    }
+}
+
+/// Finds the functions of a prefabs impl marked with `#[on_spawn]`, removing the attribute and adding a `trigger` argument
+/// observing the prefabs marker being added.  Returns the names of the functions so they can be added as observers.
+pub(crate) fn on_spawns(item: &mut ItemImpl) -> Vec<Ident> {
+    let Type::Path(self_ty) = &*item.self_ty else { return Vec::new() };
+    let Some(prefab) = self_ty.path.segments.last() else { return Vec::new() };
+
+    // the marker is next to the prefab
+    let mut marker = self_ty.path.clone();
+    let last = marker.segments.last_mut().unwrap();
+    *last = marker_ident(&prefab.ident).into();
+
+    item.items.iter_mut().filter_map(|item| {
+        let ImplItem::Fn(func) = item else { return None };
+        let len = func.attrs.len();
+        func.attrs.retain(|attr| !attr.path().is_ident("on_spawn"));
+        if func.attrs.len() == len { return None }
+
+        func.sig.inputs.insert(0, parse_quote! { trigger: bevy::prelude::Trigger<bevy::prelude::OnAdd, #marker> });
+        Some(func.sig.ident.clone())
+    }).collect()
+}
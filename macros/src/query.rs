@@ -0,0 +1,123 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse::{Parse, ParseStream}, punctuated::Punctuated, GenericArgument, Ident, PathArguments, Token, Type};
+
+/// Filters that can be recognised when a query is given without a name and so does not separate its data from its filters.
+const KNOWN_FILTERS: [&str; 5] = ["With", "Without", "Added", "Changed", "Or"];
+
+/// Wrapper system params that take query data and filters as their generic arguments.
+const QUERY_WRAPPERS: [&str; 3] = ["Query", "Single", "Populated"];
+
+/// A parsed `#[query(...)]` attribute.
+///
+/// Either `#[query(name: Data, Filter, ...)]` where the first type is the query data and every following type is a filter,
+/// or the unnamed `#[query(Data, ..., Filter, ...)]` where filters are recognised by their type.
+pub(crate) struct QueryAttr {
+    pub name: Option<Ident>,
+    pub wrapper: Option<syn::Path>,
+    pub data: Vec<Type>,
+    pub filter: Vec<Type>
+}
+
+impl Parse for QueryAttr {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        // named queries start with `name:`
+        if input.peek(Ident) && input.peek2(Token![:]) && !input.peek2(Token![::]) {
+            let name = input.parse::<Ident>()?;
+            input.parse::<Token![:]>()?;
+            let mut types = Punctuated::<Type, Token![,]>::parse_terminated(input)?.into_iter();
+            let data = types.next().ok_or_else(|| input.error("Expected query data after the query name."))?;
+            let mut filter = types.collect::<Vec<_>>();
+
+            // unpack wrappers like `Single<&Transform, With<Player>>` into their data and filters
+            let (wrapper, data) = match unwrap_query_wrapper(&data) {
+                Some((wrapper, data, wrapped_filter)) => {
+                    filter.splice(0..0, wrapped_filter);
+                    (Some(wrapper), data)
+                },
+                None => (None, data)
+            };
+
+            return Ok(Self { name: Some(name), wrapper, data: vec![data], filter });
+        }
+
+        // otherwise, sort the types into data and filters
+        let (data, filter) = Punctuated::<Type, Token![,]>::parse_terminated(input)?
+            .into_iter()
+            .partition(|ty| !is_known_filter(ty));
+        Ok(Self { name: None, wrapper: None, data, filter })
+    }
+}
+
+impl QueryAttr {
+    /// Creates the system argument for this query using the given name if the query was not named.
+    pub(crate) fn argument(&self, default_name: Ident) -> TokenStream {
+        let name = self.name.clone().unwrap_or(default_name);
+        let wrapper = self.wrapper.clone().unwrap_or_else(|| syn::parse_quote! { Query });
+        let mutability = if self.data.iter().any(is_mutable) { quote! { mut } } else { quote! {} };
+
+        let data = match self.data.as_slice() {
+            [data] => quote! { #data },
+            data => quote! { (#(#data),*) }
+        };
+
+        match self.filter.as_slice() {
+            [] => quote! { #mutability #name: #wrapper<#data> },
+            [filter] => quote! { #mutability #name: #wrapper<#data, #filter> },
+            filter => quote! { #mutability #name: #wrapper<#data, (#(#filter),*)> }
+        }
+    }
+}
+
+/// Get the identifier of the last segment of a path type, ignoring any generics.
+fn last_segment(ty: &Type) -> Option<&syn::PathSegment> {
+    match ty {
+        Type::Path(path) => path.path.segments.last(),
+        _ => None
+    }
+}
+
+fn is_known_filter(ty: &Type) -> bool {
+    last_segment(ty).is_some_and(|segment| KNOWN_FILTERS.contains(&segment.ident.to_string().as_str()))
+}
+
+/// Splits `Query<D, F>`, `Single<D, F>` or `Populated<D, F>` into the wrapper path, the data and the optional filter.
+fn unwrap_query_wrapper(ty: &Type) -> Option<(syn::Path, Type, Option<Type>)> {
+    let Type::Path(path) = ty else { return None };
+    let segment = path.path.segments.last()?;
+    if !QUERY_WRAPPERS.contains(&segment.ident.to_string().as_str()) { return None }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+
+    // get the generic types, skipping lifetimes
+    let mut types = args.args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty.clone()),
+        _ => None
+    });
+    let data = types.next()?;
+    let filter = types.next();
+
+    // strip the generics from the wrapper so they can be given again with the merged filters
+    let mut wrapper = path.path.clone();
+    wrapper.segments.last_mut()?.arguments = PathArguments::None;
+    Some((wrapper, data, filter))
+}
+
+/// Checks if the query data accesses anything mutably, either through `&mut T` or `Mut<T>`, anywhere in the type.
+pub(crate) fn is_mutable(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(reference) => reference.mutability.is_some() || is_mutable(&reference.elem),
+        Type::Tuple(tuple) => tuple.elems.iter().any(is_mutable),
+        Type::Paren(paren) => is_mutable(&paren.elem),
+        Type::Group(group) => is_mutable(&group.elem),
+        Type::Path(path) => path.path.segments.iter().any(|segment| {
+            segment.ident == "Mut" || match &segment.arguments {
+                PathArguments::AngleBracketed(args) => args.args.iter().any(|arg| match arg {
+                    GenericArgument::Type(ty) => is_mutable(ty),
+                    _ => false
+                }),
+                _ => false
+            }
+        }),
+        _ => false
+    }
+}
@@ -0,0 +1,67 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{parse::{Parse, ParseStream}, punctuated::Punctuated, Attribute, Expr, Token, Type, UseTree};
+
+/// A single entry of a `#[requires(...)]` list, either `Component` or `Component = <constructor expression>`.
+#[derive(Clone, Debug)]
+pub(crate) struct RequiredComponent {
+    pub ty: Type,
+    pub value: Option<Expr>
+}
+
+impl Parse for RequiredComponent {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let ty = input.parse()?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else { None };
+        Ok(Self { ty, value })
+    }
+}
+
+/// Parses the list of required components from a `#[requires(...)]` attribute.
+pub(crate) fn parse_requires(attr: &Attribute) -> Vec<RequiredComponent> {
+    attr.parse_args_with(Punctuated::<RequiredComponent, Token![,]>::parse_terminated)
+        .expect("Failed to parse requires attribute, expected a list of `Component` or `Component = value`.")
+        .into_iter()
+        .collect()
+}
+
+/// Converts the required components into bevys own `#[require(...)]` attribute so they are registered by `#[derive(Component)]`.
+pub(crate) fn require_attribute(required: &[RequiredComponent]) -> Attribute {
+    let required = required.iter().map(|required| {
+        let ty = &required.ty;
+        match &required.value {
+            Some(value) => quote! { #ty(|| #value) },
+            None => quote! { #ty }
+        }
+    });
+
+    syn::parse_quote! { #[require(#(#required),*)] }
+}
+
+/// Get the type brought into scope by a `use` item so required components can be registered on third party components.
+pub(crate) fn use_tree_type(tree: &UseTree) -> Type {
+    match tree {
+        UseTree::Path(path) => use_tree_type(&path.tree),
+        UseTree::Name(name) => {
+            let ident = &name.ident;
+            syn::parse_quote! { #ident }
+        },
+        UseTree::Rename(rename) => {
+            let ident = &rename.rename;
+            syn::parse_quote! { #ident }
+        },
+        _ => panic!("Requires attribute can only be applied to a `use` of a single component.")
+    }
+}
+
+/// Creates the app extension that registers the required component at runtime.
+pub(crate) fn register_required(component: &Type, required: &RequiredComponent) -> TokenStream {
+    let ty = &required.ty;
+    match &required.value {
+        Some(value) => quote! { .register_required_components_with::<#component, #ty>(|| #value) },
+        None => quote! { .register_required_components::<#component, #ty>() }
+    }
+}
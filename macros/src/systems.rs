@@ -2,12 +2,19 @@ use std::collections::HashMap;
 
 use convert_case::{Case, Casing};
 use proc_macro2::{Span, TokenStream, TokenTree};
-use syn::{punctuated::Punctuated, Expr, FnArg, Ident, ItemFn, Meta, Pat, ReturnType};
+use syn::{punctuated::Punctuated, Expr, FnArg, GenericArgument, Ident, ItemFn, Meta, Pat, PathArguments, ReturnType, Type};
 use quote::quote;
 
+use crate::{inject::{inject, push_argument}, iteration::OnAttr, query::QueryAttr, trace::{self, TraceOptions}};
+
 #[derive(Default)]
 pub struct SystemProcessor {
+    plugin: String,
+    trace: Option<TraceOptions>,
+    timed: bool,
     definitions: HashMap<Ident, FunctionDef>,
+    resources: HashMap<Ident, Vec<Ident>>,
+    produced: Vec<Type>,
     impl_functions: Vec<ItemFn>,
     base_functions: Vec<ItemFn>
 }
@@ -17,7 +24,8 @@ enum FunctionDef {
     Build,
     ResourceFactory,
     System(Expr, SystemOrdering),
-    Observer
+    Observer,
+    OneShot(Type, Type)
 }
 
 enum SystemOrdering {
@@ -41,10 +49,16 @@ enum Priority {
 }
 
 impl SystemProcessor {
+    /// Creates a processor for the systems of the plugin, tracing all of them if `#[plugin(trace)]` was given.
+    pub(crate) fn new(plugin: &Ident, trace: Option<TraceOptions>) -> Self {
+        Self { plugin: plugin.to_string(), trace, ..Default::default() }
+    }
+
     pub fn process_item_fn(&mut self, mut item: ItemFn) {
         // define default function type
         let mut definition = FunctionDef::Impl;
         let mut query_count = 1;
+        let mut trace = None;
 
         // run through each attribute to modify the existing function
         for attr in item.attrs.clone() {
@@ -52,7 +66,7 @@ impl SystemProcessor {
             let attr_name = attr.path().get_ident().expect("Failed to unwrap attr_name");
             let attr_name = attr_name.to_string();
             let attr_name = attr_name.as_str();
-            let tokens = meta_to_strings(attr.meta);
+            let tokens = meta_to_strings(attr.meta.clone());
 
             // translate some attributes for backwards compatability
             let (attr_name, tokens) = match attr_name {
@@ -71,13 +85,6 @@ impl SystemProcessor {
                     ("system", vec)
                 },
 
-                "query" => {
-                    let mut vec = vec![format!("query{query_count}"), ",".to_string()];
-                    query_count += 1;
-                    vec.extend(tokens);
-                    ("named_query", vec)
-                },
-
                 "added" => {
                     let name = tokens.last().unwrap();
                     ("trigger", vec!["bevy::prelude::OnAdd".to_string(), ",".to_string(), name.clone()])
@@ -95,6 +102,7 @@ impl SystemProcessor {
             match attr_name {
                 "build" => { definition = FunctionDef::Build; }
                 "resource_factory" => { definition = FunctionDef::ResourceFactory; }
+                "one_shot" => { definition = FunctionDef::OneShot(syn::parse_quote! { () }, syn::parse_quote! { () }); }
 
                 "resource_system" => { 
                     // add system definition
@@ -118,13 +126,14 @@ impl SystemProcessor {
                     let commands = if commands.is_some() { 
                         commands.unwrap() 
                     } else {
-                        item.sig.inputs.push(syn::parse2(quote! {
+                        push_argument(&mut item, syn::parse2(quote! {
                             mut commands: Commands
                         }).unwrap());
                         Ident::new("commands", Span::call_site())
                     };
 
-                    // remove return
+                    // remember the resource for the manifest and remove return
+                    if let ReturnType::Type(_, ty) = &item.sig.output { self.produced.push(*ty.clone()); }
                     item.sig.output = ReturnType::Default;
 
                     // add code to add resource
@@ -145,7 +154,7 @@ impl SystemProcessor {
                     if exec == "enter" || exec == "exit" {
                         let input = tokens[1].to_string();
                         let input = Ident::new(input.as_str(), Span::call_site());
-                        item.sig.inputs.push(syn::parse2(quote! {
+                        push_argument(&mut item, syn::parse2(quote! {
                             current: Res<State<#input>>
                         }).unwrap());
                     }
@@ -166,7 +175,7 @@ impl SystemProcessor {
                     let event = Ident::new(event_name.as_str(), Span::call_site());
                     
                     // add argument for event
-                    item.sig.inputs.push(syn::parse2(quote! {
+                    push_argument(&mut item, syn::parse2(quote! {
                         mut #event_arg: EventReader<#event>
                     }).expect("Failed to unwrap event argument."));
 
@@ -248,7 +257,7 @@ impl SystemProcessor {
                     item.sig.inputs = vec;
                 }
 
-                "named_query" => {
+                "query" => {
                     // if def has not been set yet, set to update
                     if matches!(definition, FunctionDef::Impl) {
                         definition = FunctionDef::System(
@@ -257,77 +266,33 @@ impl SystemProcessor {
                         );
                     }
 
-                    // build query tokens by splitting my commas and merging
-                    // then, sort tokens into query and filter
-                    let (mut query, mut filter): (Vec<_>, Vec<_>) = tokens[2..]
-                        .split(|s| s == ",")
-                        .map(|a| a.join(" "))
-                        .partition(|a| {
-                            !(
-                                a.starts_with("With <") || 
-                                a.starts_with("Without <") || 
-                                a.starts_with("Added <") || 
-                                a.starts_with("Removed <") || 
-                                a.starts_with("Changed <")
-                            )
-                        });
-                    
-                    // get some metadata
-                    let ident = Ident::new(tokens[0].as_str(), Span::call_site());
-                    let mutable = query.iter().any(|s| s.contains("& mut"));
+                    // parse the query, unnamed queries are named by their order
+                    let query: QueryAttr = attr.parse_args().expect("Failed to parse query attribute.");
+                    if query.data.is_empty() { continue; }
+                    let default_name = Ident::new(format!("query{query_count}").as_str(), Span::call_site());
+                    if query.name.is_none() { query_count += 1; }
 
-                    // set query and filter
-                    let query = query.drain(..).map(|a| syn::parse2::<syn::Type>(a.parse().unwrap()).unwrap()).collect::<Vec<_>>();
-                    let filter = filter.drain(..).map(|a| syn::parse2::<syn::Type>(a.parse().unwrap()).unwrap()).collect::<Vec<_>>();
-                    
-                    // stop if query empty
-                    if query.is_empty() { continue; }
-
-                    // add query arguments
-                    if filter.is_empty() {
-                        if mutable {
-                            item.sig.inputs.push(syn::parse2(quote! {
-                                mut #ident: Query<(#(#query),*)>
-                            }).unwrap());
-                        } else {
-                            item.sig.inputs.push(syn::parse2(quote! {
-                                #ident: Query<(#(#query),*)>
-                            }).unwrap());
-                        }
-                    } else {
-                        if mutable {
-                            item.sig.inputs.push(syn::parse2(quote! {
-                                mut #ident: Query<(#(#query),*), (#(#filter),*)>
-                            }).unwrap());
-                        } else {
-                            item.sig.inputs.push(syn::parse2(quote! {
-                                #ident: Query<(#(#query),*), (#(#filter),*)>
-                            }).unwrap());
-                        }
-                    }
+                    // add query argument
+                    push_argument(&mut item, syn::parse2(query.argument(default_name)).expect("Failed to unwrap query argument."));
+                }
+
+                "res" | "res_mut" | "local" | "commands" | "assets" => inject(&mut item, attr_name, &attr),
+
+                "trace" => {
+                    trace = Some(match attr.meta {
+                        Meta::List(_) => attr.parse_args().expect("Failed to parse trace attribute, expected `timing`."),
+                        _ => TraceOptions::default()
+                    });
                 }
 
                 "on" => {
-                    let mutable = tokens.iter().any(|a| a == "mut");
-                    let ident = Ident::new(tokens.last().unwrap().as_str(), Span::call_site());
-                    let block = item.block;
-                    if mutable {
-                        item.block = syn::parse2(quote! {
-                            {
-                                for #ident in #ident.iter_mut() {
-                                    #block
-                                }
-                            }
-                        }).unwrap();
-                    } else {
-                        item.block = syn::parse2(quote! {
-                            {
-                                for #ident in #ident.iter() {
-                                    #block
-                                }
-                            }
-                        }).unwrap();
-                    }
+                    let on: OnAttr = attr.parse_args().expect("Failed to parse on attribute.");
+                    let block = on.wrap(&item.block);
+                    item.block = syn::parse2(quote! {
+                        {
+                            #block
+                        }
+                    }).expect("Failed to unwrap on block.");
                 }
 
                 _ => panic!("Unknown plugin attribute {:?}", attr_name)
@@ -337,25 +302,62 @@ impl SystemProcessor {
         // remove all attributes
         item.attrs.clear();
 
+        // traced functions are wrapped last so that the span covers everything the other attributes added
+        let traceable = matches!(definition, FunctionDef::System(_, _) | FunctionDef::Observer | FunctionDef::OneShot(_, _));
+        if let Some(options) = trace.or(self.trace.filter(|_| traceable)) {
+            let name = format!("{}::{}", self.plugin, item.sig.ident);
+            trace::wrap(&mut item, &name, options);
+            self.timed |= options.timing;
+        }
+
+        // one shot systems store their input and output types so their system id can be stored
+        if let FunctionDef::OneShot(ref mut input, ref mut output) = definition {
+            if let Some(FnArg::Typed(arg)) = item.sig.inputs.first() {
+                if let Type::Path(path) = &*arg.ty {
+                    let is_input = path.path.segments.last()
+                        .is_some_and(|segment| segment.ident == "In" || segment.ident == "InRef" || segment.ident == "InMut");
+                    if is_input { *input = *arg.ty.clone(); }
+                }
+            }
+            if let ReturnType::Type(_, ty) = &item.sig.output {
+                *output = *ty.clone();
+            }
+        }
+
+        // resource factories insert the resource they return
+        if let (FunctionDef::ResourceFactory, ReturnType::Type(_, ty)) = (&definition, &item.sig.output) {
+            self.produced.push(*ty.clone());
+        }
+
+        // remember the resources the system reads so it can be gated on scoped resources existing
+        self.resources.insert(item.sig.ident.clone(), resource_types(&item));
+
         // save definiton and function item
         let item_list = match &definition {
             FunctionDef::Impl => &mut self.impl_functions,
             FunctionDef::Build => &mut self.impl_functions,
             FunctionDef::ResourceFactory => &mut self.base_functions,
             FunctionDef::System(_, _) => &mut self.base_functions,
-            FunctionDef::Observer => &mut self.base_functions
+            FunctionDef::Observer => &mut self.base_functions,
+            FunctionDef::OneShot(_, _) => &mut self.base_functions
         };
         self.definitions.insert(item.sig.ident.clone(), definition);
         item_list.push(item);
     }
 
-    pub fn apply_app_exts(&mut self, app_exts: &mut TokenStream) {
+    pub fn apply_app_exts(&mut self, app_exts: &mut TokenStream, scoped_resources: &[Ident]) {
         // sort systems by expression
-        let mut prio_systems = HashMap::<Expr, Vec<(Priority, Ident)>>::new();
+        let mut prio_systems = HashMap::<Expr, Vec<(Priority, TokenStream)>>::new();
         let mut unordered_systems = HashMap::<Expr, Vec<Expr>>::new();
 
         // for each definition, add only systems
         for (name, def) in self.definitions.iter_mut() {
+            // systems reading a scoped resource only run while it exists, observers can not take run conditions so they are left ungated
+            let gates = self.resources.get(name).into_iter().flatten().filter(|resource| scoped_resources.contains(resource));
+            let gates = quote! { #(.run_if(bevy::prelude::resource_exists::<#gates>))* };
+            let ident = name;
+            let name = quote! { #name #gates };
+
             match def {
                 // add systems to systems tracking map
                 FunctionDef::System(expr, priority) => {
@@ -398,13 +400,14 @@ impl SystemProcessor {
                             }
                         },
 
+                        // run conditions need a system without an output, so the piped pair is gated as a whole
                         SystemOrdering::Pipe(pipe) => {
                             if unordered_systems.contains_key(&expr) {
                                 unordered_systems.get_mut(&expr)
                                     .expect("Failed to unwrap unordered systems get.")
-                                    .push(syn::parse2(quote! { #name.pipe(#pipe) }).unwrap());
+                                    .push(syn::parse2(quote! { #ident.pipe(#pipe) #gates }).unwrap());
                             } else {
-                                unordered_systems.insert(expr.clone(), vec![syn::parse2(quote! { #name.pipe(#pipe) }).unwrap()]);
+                                unordered_systems.insert(expr.clone(), vec![syn::parse2(quote! { #ident.pipe(#pipe) #gates }).unwrap()]);
                             }
                         },
 
@@ -476,13 +479,115 @@ impl SystemProcessor {
                 FunctionDef::Build => Some(ident.clone()),
                 _ => None
             }).collect::<Vec<_>>();
-        builds.extend(quote! { #(self.#build_funcs(app);)* })
+        builds.extend(quote! { #(self.#build_funcs(app);)* });
+
+        if self.timed {
+            builds.extend(quote! { app.init_resource::<mod_plugins::resources::SystemTimings>(); });
+        }
+    }
+
+    /// Registers each one shot system and stores their ids in a resource named after the plugin with `Systems` appended.
+    pub fn apply_one_shots(&self, plugin: &Ident, builds: &mut TokenStream, output: &mut TokenStream) {
+        let one_shots = self.definitions
+            .iter()
+            .filter_map(|(ident, def)| match def {
+                FunctionDef::OneShot(input, output) => Some((ident, input, output)),
+                _ => None
+            }).collect::<Vec<_>>();
+        if one_shots.is_empty() { return }
+
+        let resource = Ident::new(format!("{plugin}Systems").as_str(), Span::call_site());
+        let fields = one_shots.iter().map(|(ident, input, output)| quote! {
+            pub #ident: bevy::ecs::system::SystemId<#input, #output>
+        });
+        let names = one_shots.iter().map(|(ident, _, _)| ident);
+
+        output.extend(quote! {
+            #[derive(bevy::prelude::Resource, Clone, Copy, Debug)]
+            pub struct #resource {
+                #(#fields),*
+            }
+        });
+        builds.extend(quote! {
+            let systems = #resource {
+                #(#names: app.register_system(#names)),*
+            };
+            app.insert_resource(systems);
+        });
+    }
+
+    /// Describes each system for the plugins manifest, sorted by name.
+    pub fn system_manifests(&self, scoped_resources: &[Ident]) -> Vec<TokenStream> {
+        let mut systems = self.definitions.iter()
+            .filter_map(|(name, def)| {
+                let (schedule, ordering) = match def {
+                    FunctionDef::System(expr, ordering) => (token_string(quote! { #expr }), ordering),
+                    FunctionDef::OneShot(_, _) => ("OneShot".to_string(), &SystemOrdering::None),
+                    _ => return None
+                };
+
+                let mut run_conditions = self.resources.get(name).into_iter().flatten()
+                    .filter(|resource| scoped_resources.contains(resource))
+                    .map(|resource| format!("resource_exists::<{resource}>"))
+                    .collect::<Vec<_>>();
+                let ordering = match ordering {
+                    SystemOrdering::None => String::new(),
+                    SystemOrdering::Priority(priority) => format!("priority({})", priority.name()),
+                    SystemOrdering::Before(before) => format!("before({before})"),
+                    SystemOrdering::After(after) => format!("after({after})"),
+                    SystemOrdering::Pipe(pipe) => format!("pipe({pipe})"),
+                    SystemOrdering::InState(state) => {
+                        run_conditions.insert(0, format!("in_state({})", token_string(quote! { #state })));
+                        String::new()
+                    }
+                };
+                Some((name.to_string(), schedule, ordering, run_conditions))
+            })
+            .collect::<Vec<_>>();
+        systems.sort();
+
+        systems.into_iter().map(|(name, schedule, ordering, run_conditions)| quote! {
+            mod_plugins::resources::SystemManifest {
+                name: #name,
+                schedule: #schedule,
+                ordering: #ordering,
+                run_conditions: &[#(#run_conditions),*]
+            }
+        }).collect()
+    }
+
+    /// Get the names of the observers, sorted by name.
+    pub fn observer_names(&self) -> Vec<String> {
+        let mut observers = self.definitions.iter()
+            .filter(|(_, def)| matches!(def, FunctionDef::Observer))
+            .map(|(name, _)| name.to_string())
+            .collect::<Vec<_>>();
+        observers.sort();
+        observers
+    }
+
+    /// Get the resources inserted by resource systems and factories.
+    pub fn produced_resources(&self) -> Vec<String> {
+        self.produced.iter().map(|ty| token_string(quote! { #ty })).collect()
     }
 
     pub fn impl_functions(&self) -> &[ItemFn] { return &self.impl_functions; }
     pub fn base_functions(&self) -> &[ItemFn] { return &self.base_functions; }
 }
 
+impl Priority {
+    fn name(&self) -> String {
+        match self {
+            Priority::LOWEST => "LOWEST".to_string(),
+            Priority::LOW => "LOW".to_string(),
+            Priority::NORMAL => "NORMAL".to_string(),
+            Priority::HIGH => "HIGH".to_string(),
+            Priority::HIGHEST => "HIGHEST".to_string(),
+            Priority::CUSTOM(priority) => format!("CUSTOM({priority})")
+        }
+    }
+}
+
 fn build_system_enum_variant(tokens: &[String]) -> FunctionDef {
     let expr: syn::Expr = match tokens[0].as_str() {
         "update" => syn::parse2(quote! { Update }).expect("Failed to unwrap Update system expr."),
@@ -502,6 +607,21 @@ fn build_system_enum_variant(tokens: &[String]) -> FunctionDef {
     return FunctionDef::System(expr, SystemOrdering::None);
 }
 
+/// Get the types of the resources a system reads through `Res` or `ResMut` arguments.
+fn resource_types(item: &ItemFn) -> Vec<Ident> {
+    item.sig.inputs.iter().filter_map(|arg| {
+        let FnArg::Typed(arg) = arg else { return None };
+        let Type::Path(path) = &*arg.ty else { return None };
+        let segment = path.path.segments.last()?;
+        if segment.ident != "Res" && segment.ident != "ResMut" { return None }
+        let PathArguments::AngleBracketed(args) = &segment.arguments else { return None };
+        args.args.iter().find_map(|arg| match arg {
+            GenericArgument::Type(Type::Path(ty)) => ty.path.segments.last().map(|segment| segment.ident.clone()),
+            _ => None
+        })
+    }).collect()
+}
+
 fn meta_to_strings(meta: Meta) -> Vec<String> {
     match meta {
         Meta::List(list) => tokens_to_strings(list.tokens),
@@ -509,6 +629,11 @@ fn meta_to_strings(meta: Meta) -> Vec<String> {
     }
 }
 
+/// Get tokens as a compact string, as used to name types and schedules in the manifest.
+pub fn token_string(tokens: TokenStream) -> String {
+    tokens.to_string().replace(' ', "")
+}
+
 pub fn tokens_to_strings(tokens: TokenStream) -> Vec<String> {
     return tokens
         .into_iter()
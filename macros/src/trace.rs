@@ -0,0 +1,77 @@
+use quote::quote;
+use syn::{parse::{Parse, ParseStream}, FnArg, Ident, ItemFn, Pat, Token, Type};
+
+use crate::inject::push_argument;
+
+/// The options of `#[trace]` and `#[plugin(trace)]`, like `trace(timing)`.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct TraceOptions {
+    pub timing: bool
+}
+
+impl Parse for TraceOptions {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut options = Self::default();
+
+        while !input.is_empty() {
+            let option = input.parse::<Ident>()?;
+            match option.to_string().as_str() {
+                "timing" => options.timing = true,
+                _ => return Err(syn::Error::new(option.span(), "Unknown trace option, expected `timing`."))
+            }
+
+            if input.is_empty() { break }
+            input.parse::<Token![,]>()?;
+        }
+
+        Ok(options)
+    }
+}
+
+/// Wraps the body of the function in a span with the given name, timing each run into `SystemTimings` if asked.
+/// Both are held by guards, so early returns are still traced and timed.
+/// Exclusive systems cannot take `SystemTimings` as a parameter, so their body is run as a closure and timed around it.
+pub(crate) fn wrap(item: &mut ItemFn, name: &str, options: TraceOptions) {
+    let block = item.block.clone();
+    let body = match (options.timing, world_argument(item)) {
+        (false, _) => quote! { #block },
+        (true, Some(world)) => quote! {
+            let __start = std::time::Instant::now();
+            #[allow(clippy::redundant_closure_call)]
+            let __result = (|| #block)();
+            #world.resource::<mod_plugins::resources::SystemTimings>().record(#name, __start.elapsed());
+            __result
+        },
+        (true, None) => {
+            push_argument(item, syn::parse2(quote! {
+                __system_timings: bevy::prelude::Res<mod_plugins::resources::SystemTimings>
+            }).expect("Failed to unwrap system timings argument."));
+            quote! {
+                let __timer = __system_timings.start(#name);
+                #block
+            }
+        }
+    };
+
+    item.block = syn::parse2(quote! {
+        {
+            let __span = bevy::utils::tracing::info_span!(#name).entered();
+            #body
+        }
+    }).expect("Failed to unwrap trace block.");
+}
+
+/// Get the name of the `&mut World` argument of an exclusive system.
+fn world_argument(item: &ItemFn) -> Option<Ident> {
+    item.sig.inputs.iter().find_map(|arg| {
+        let FnArg::Typed(arg) = arg else { return None };
+        let Type::Reference(reference) = &*arg.ty else { return None };
+        let Type::Path(path) = &*reference.elem else { return None };
+        let is_world = path.path.segments.last().is_some_and(|segment| segment.ident == "World");
+        if reference.mutability.is_none() || !is_world { return None }
+        match &*arg.pat {
+            Pat::Ident(ident) => Some(ident.ident.clone()),
+            _ => None
+        }
+    })
+}
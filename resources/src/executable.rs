@@ -0,0 +1,172 @@
+use std::{any::{Any, TypeId}, collections::HashMap};
+use bevy::{ecs::system::{BoxedSystem, SystemInput}, prelude::*};
+
+/// A trait to be implemented by structs that need to be able to execute something on the client.
+pub trait Executable<O> {
+    fn execute(self: Box<Self>, world: &mut World) -> O;
+}
+
+/// Initialized executable systems keyed by the executable type, so their `Local`s and change detection persist between executions.
+#[derive(Resource, Default)]
+pub struct ExecutableSystems(HashMap<TypeId, Box<dyn Any + Send + Sync>>);
+
+impl ExecutableSystems {
+    /// Runs the system of the executable `T`, initializing it on its first run and caching it for the next.
+    pub fn run<T: 'static, I: SystemInput + 'static, O: 'static, M>(
+        world: &mut World,
+        system: impl IntoSystem<I, O, M>,
+        input: I::Inner<'_>
+    ) -> O {
+        // take the cached system out of the world so it can be run on the world
+        let cached = world.get_resource_or_insert_with(ExecutableSystems::default)
+            .0
+            .remove(&TypeId::of::<T>())
+            .and_then(|system| system.downcast::<BoxedSystem<I, O>>().ok());
+        let mut system = match cached {
+            Some(system) => *system,
+            None => {
+                let mut system: BoxedSystem<I, O> = Box::new(IntoSystem::into_system(system));
+                system.initialize(world);
+                system
+            }
+        };
+
+        // run and return the system to the cache
+        let response = system.run(input, world);
+        world.get_resource_or_insert_with(ExecutableSystems::default)
+            .0
+            .insert(TypeId::of::<T>(), Box::new(system));
+
+        response
+    }
+}
+
+/// A type erased executable, allowing executables of different types to be stored together.
+pub type BoxedExecutable<O = ()> = Box<dyn Executable<O> + Send + Sync>;
+
+/// Allows executables to be executed directly on the `World`.
+pub trait WorldExecuteExt {
+    /// Executes the given executable, which may be type erased, returning its response.
+    fn execute<O, E: Executable<O> + ?Sized>(&mut self, executable: Box<E>) -> O;
+}
+
+impl WorldExecuteExt for World {
+    fn execute<O, E: Executable<O> + ?Sized>(&mut self, executable: Box<E>) -> O {
+        executable.execute(self)
+    }
+}
+
+/// Allows executables to be executed from systems through their `Commands`.
+pub trait CommandsExecuteExt {
+    /// Queues the given executable to be executed when the commands are applied, its response is dropped.
+    fn execute<O: 'static, E: Executable<O> + Send + Sync + ?Sized + 'static>(&mut self, executable: Box<E>);
+
+    /// Defers the given executable until the `ExecutablePlugin` next runs pending executables, returning a handle entity.
+    /// When the executable has been executed, `ExecutableCompleted` is triggered on the handle with the response and the handle is despawned.
+    /// Despawning the handle before then cancels the executable.
+    fn queue_executable<O: Send + Sync + 'static, E: Executable<O> + Send + Sync + ?Sized + 'static>(&mut self, executable: Box<E>) -> EntityCommands<'_>;
+}
+
+impl CommandsExecuteExt for Commands<'_, '_> {
+    fn execute<O: 'static, E: Executable<O> + Send + Sync + ?Sized + 'static>(&mut self, executable: Box<E>) {
+        self.queue(move |world: &mut World| {
+            executable.execute(world);
+        });
+    }
+
+    fn queue_executable<O: Send + Sync + 'static, E: Executable<O> + Send + Sync + ?Sized + 'static>(&mut self, executable: Box<E>) -> EntityCommands<'_> {
+        let handle = self.spawn(PendingExecutable).id();
+        self.queue(move |world: &mut World| {
+            world.get_resource_or_insert_with(PendingExecutables::default).0.push((handle, Box::new(move |world: &mut World| {
+                let response = executable.execute(world);
+                world.trigger_targets(ExecutableCompleted(response), handle);
+            })));
+        });
+        self.entity(handle)
+    }
+}
+
+/// Marks the handle entity of an executable queued with `queue_executable` that has not been executed yet.
+#[derive(Component, Default)]
+pub struct PendingExecutable;
+
+/// Triggered on the handle entity of a queued executable once it has been executed, holding its response.
+#[derive(Event)]
+pub struct ExecutableCompleted<O: Send + Sync + 'static>(pub O);
+
+type PendingExecute = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// Executables queued with `queue_executable` with their handle entity, in the order they were queued.
+#[derive(Resource, Default)]
+pub struct PendingExecutables(Vec<(Entity, PendingExecute)>);
+
+/// Executes every pending executable whose handle still exists, then despawns the handle unless an observer already has.
+pub fn run_pending_executables(world: &mut World) {
+    let Some(mut pending) = world.get_resource_mut::<PendingExecutables>() else { return };
+    let pending = std::mem::take(&mut pending.0);
+    for (handle, execute) in pending {
+        // a despawned handle cancels its executable
+        if world.get_entity(handle).is_err() { continue }
+
+        execute(world);
+        if let Ok(handle) = world.get_entity_mut(handle) {
+            handle.despawn();
+        }
+    }
+}
+
+/// A queue of executables, for example actions received from a server, that is drained every frame by the `ExecutablePlugin`.
+#[derive(Resource, Default)]
+pub struct ExecutableQueue(Vec<BoxedExecutable>);
+
+impl ExecutableQueue {
+    /// Adds an executable to the end of the queue.
+    pub fn push(&mut self, executable: BoxedExecutable) {
+        self.0.push(executable);
+    }
+
+    /// Get the number of executables waiting in the queue.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Check if there are no executables waiting in the queue.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl Extend<BoxedExecutable> for ExecutableQueue {
+    fn extend<I: IntoIterator<Item = BoxedExecutable>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+/// Executes every executable in the `ExecutableQueue` in order, executables queued while draining are executed next frame.
+pub fn drain_executable_queue(world: &mut World) {
+    let queue = std::mem::take(&mut world.resource_mut::<ExecutableQueue>().0);
+    for executable in queue {
+        world.execute(executable);
+    }
+}
+
+/// Adds the `ExecutableQueue`, draining it and running pending executables in `PreUpdate` every frame.
+pub struct ExecutablePlugin;
+
+impl Plugin for ExecutablePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExecutableQueue>()
+            .init_resource::<PendingExecutables>()
+            .add_systems(PreUpdate, (drain_executable_queue, run_pending_executables).chain());
+    }
+}
+
+/// Implemented for an executable enum by the executable of each variant, `V` is the marker of the variant and `I` its fields.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` has no executable for the variant `{V}`",
+    label = "missing executable for this variant",
+    note = "add a function marked with `#[executable(Enum::Variant)]` for each variant"
+)]
+pub trait ExecutableVariant<V, I> {
+    fn execute_variant(input: I, world: &mut World);
+}
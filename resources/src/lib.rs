@@ -1,11 +1,37 @@
-use std::{fmt::Debug, ops::Deref};
+use std::ops::{Deref, DerefMut};
 use bevy::prelude::*;
 
-#[derive(Resource, Clone, Debug)]
-pub struct Current<T: Clone + Debug>(T);
+mod executable;
+pub use executable::*;
+
+mod manifest;
+pub use manifest::*;
+
+mod schedule_graph;
+pub use schedule_graph::*;
+
+mod scope;
+pub use scope::*;
+
+mod timing;
+pub use timing::*;
+
+#[cfg(feature = "serde")]
+mod serialized;
+#[cfg(feature = "serde")]
+pub use serialized::*;
+
+#[cfg(feature = "prefab_assets")]
+mod prefab;
+#[cfg(feature = "prefab_assets")]
+pub use prefab::*;
+
+#[derive(Resource, Reflect, Clone, Debug)]
+#[reflect(Resource)]
+pub struct Current<T: Send + Sync + 'static>(T);
 
 /// Allow Current to be dereferenced so it implements all the functions of the wrapped type
-impl <T: Clone + Debug> Deref for Current<T> {
+impl <T: Send + Sync + 'static> Deref for Current<T> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -13,7 +39,14 @@ impl <T: Clone + Debug> Deref for Current<T> {
     }
 }
 
-impl <T: Clone + Debug> Current<T> {
+/// Allow Current to be mutably dereferenced so the wrapped type can be modified in place
+impl <T: Send + Sync + 'static> DerefMut for Current<T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl <T: Send + Sync + 'static> Current<T> {
     /// Creates a new Current instance to wrap the given type
     pub fn new(input: T) -> Self { Self(input) }
 
@@ -33,13 +66,33 @@ impl <T: Clone + Debug> Current<T> {
     }
 }
 
-/// A trait to be implemented by structs that need to be able to execute something on the client.
-pub trait Executable<O> {
-    fn execute(self: Box<Self>, world: &mut World) -> O;
+/// Allows a `Current` to be added to the `World` for the duration of a function.
+pub trait WorldCurrentExt {
+    /// Inserts the given value as `Current<T>` while running the function, removing it afterwards even if the function panics.
+    /// Any `Current<T>` that was already in the world is restored afterwards, so this can be used re-entrantly.
+    fn with_current<T: Send + Sync + 'static, R>(&mut self, value: T, func: impl FnOnce(&mut World) -> R) -> R;
+}
+
+impl WorldCurrentExt for World {
+    fn with_current<T: Send + Sync + 'static, R>(&mut self, value: T, func: impl FnOnce(&mut World) -> R) -> R {
+        let previous = self.remove_resource::<Current<T>>();
+        self.insert_resource(Current::new(value));
+        let guard = CurrentGuard { world: self, previous };
+        func(&mut *guard.world)
+    }
 }
 
-#[derive(Component, Default)]
-pub struct ScopeGlobal;
+/// Removes the `Current<T>` when dropped, restoring the previous one if there was one.
+struct CurrentGuard<'w, T: Send + Sync + 'static> {
+    world: &'w mut World,
+    previous: Option<Current<T>>
+}
 
-#[derive(Component, Default)]
-pub struct ScopeLocal<S: States + Default>(pub S);
+impl<T: Send + Sync + 'static> Drop for CurrentGuard<'_, T> {
+    fn drop(&mut self) {
+        self.world.remove_resource::<Current<T>>();
+        if let Some(previous) = self.previous.take() {
+            self.world.insert_resource(previous);
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use bevy::prelude::*;
+
+/// Everything a `#[plugin]` registers with the `App`, available on the plugin as its `MANIFEST` constant.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PluginManifest {
+    pub name: &'static str,
+    /// The path of the module the plugin was declared in, which its systems are found under.
+    pub module: &'static str,
+    pub systems: &'static [SystemManifest],
+    pub observers: &'static [&'static str],
+    pub events: &'static [&'static str],
+    pub states: &'static [&'static str],
+    pub resources: &'static [&'static str],
+    pub registered: &'static [&'static str]
+}
+
+/// A system added by a plugin, with the schedule it runs in.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SystemManifest {
+    pub name: &'static str,
+    pub schedule: &'static str,
+    /// How the system is ordered, like `before(other)` or `priority(HIGH)`, empty when it is not ordered.
+    pub ordering: &'static str,
+    pub run_conditions: &'static [&'static str]
+}
+
+/// The manifests of every plugin added to the `App` after this resource was initialized.
+/// Initialize it before adding plugins to collect their manifests.
+#[derive(Resource, Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PluginManifests(pub Vec<PluginManifest>);
+
+impl PluginManifests {
+    /// Iterates the manifests in the order their plugins were added.
+    pub fn iter(&self) -> impl Iterator<Item = &PluginManifest> {
+        self.0.iter()
+    }
+
+    /// Get the manifest of the plugin with the given name.
+    pub fn get(&self, name: &str) -> Option<&PluginManifest> {
+        self.0.iter().find(|manifest| manifest.name == name)
+    }
+
+    /// Dumps every manifest as pretty printed JSON.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&self.0).expect("Failed to serialize plugin manifests.")
+    }
+}
+
+/// Allows plugins to add their manifest to the `App`.
+pub trait AppPluginManifestExt {
+    /// Adds the manifest to `PluginManifests` if it has been initialized.
+    fn add_plugin_manifest(&mut self, manifest: PluginManifest) -> &mut Self;
+}
+
+impl AppPluginManifestExt for App {
+    fn add_plugin_manifest(&mut self, manifest: PluginManifest) -> &mut Self {
+        if let Some(mut manifests) = self.world_mut().get_resource_mut::<PluginManifests>() {
+            manifests.0.push(manifest);
+        }
+        self
+    }
+}
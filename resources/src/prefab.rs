@@ -0,0 +1,222 @@
+use std::{any::TypeId, fmt::Display, marker::PhantomData};
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadState},
+    ecs::{change_detection::{CHECK_TICK_THRESHOLD, MAX_CHANGE_AGE}, component::Tick, event::EventCursor},
+    prelude::*,
+    reflect::{serde::TypedReflectDeserializer, GetTypeRegistration, ReflectRef, TypeRegistryArc, Typed},
+    transform::TransformSystem,
+    utils::HashSet
+};
+use serde::de::DeserializeSeed;
+
+/// A prefab that can be loaded from a RON file, implemented for prefabs marked with `#[prefab(asset)]`.
+pub trait PrefabAsset: Asset + Bundle + Struct + FromReflect + Typed + GetTypeRegistration {}
+
+impl<T: Asset + Bundle + Struct + FromReflect + Typed + GetTypeRegistration> PrefabAsset for T {}
+
+/// Loads prefab assets from `.prefab.ron` files by deserializing them through their reflected description.
+pub struct PrefabLoader<T: PrefabAsset> {
+    registry: TypeRegistryArc,
+    _marker: PhantomData<fn() -> T>
+}
+
+impl<T: PrefabAsset> FromWorld for PrefabLoader<T> {
+    fn from_world(world: &mut World) -> Self {
+        Self { registry: world.resource::<AppTypeRegistry>().0.clone(), _marker: PhantomData }
+    }
+}
+
+impl<T: PrefabAsset> AssetLoader for PrefabLoader<T> {
+    type Asset = T;
+    type Settings = ();
+    type Error = PrefabLoaderError;
+
+    async fn load(&self, reader: &mut dyn Reader, _settings: &(), _load_context: &mut LoadContext<'_>) -> Result<T, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await.map_err(PrefabLoaderError::read)?;
+
+        // deserialize the description of the prefab through the type registry
+        let registry = self.registry.read();
+        let registration = registry.get(TypeId::of::<T>())
+            .ok_or_else(|| PrefabLoaderError::Unregistered(T::type_path()))?;
+        let mut deserializer = ron::de::Deserializer::from_bytes(&bytes).map_err(PrefabLoaderError::parse)?;
+        let reflected = TypedReflectDeserializer::new(registration, &registry)
+            .deserialize(&mut deserializer)
+            .map_err(PrefabLoaderError::parse)?;
+
+        T::from_reflect(reflected.as_partial_reflect()).ok_or_else(|| PrefabLoaderError::Unregistered(T::type_path()))
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["prefab.ron"]
+    }
+}
+
+/// An error from loading a prefab asset.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PrefabLoaderError {
+    Read(String),
+    Parse(String),
+    /// The prefab type was not registered with the type registry, so it cannot be deserialized.
+    Unregistered(&'static str)
+}
+
+impl PrefabLoaderError {
+    fn read(error: impl Display) -> Self { Self::Read(error.to_string()) }
+    fn parse(error: impl Display) -> Self { Self::Parse(error.to_string()) }
+}
+
+impl Display for PrefabLoaderError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read(error) => write!(f, "Failed to read prefab: {error}"),
+            Self::Parse(error) => write!(f, "Failed to parse prefab: {error}"),
+            Self::Unregistered(path) => write!(f, "Prefab {path} is not registered as a reflected type")
+        }
+    }
+}
+
+impl std::error::Error for PrefabLoaderError {}
+
+/// An entity spawned from a prefab asset, filled with the prefabs components once the asset has loaded.
+#[derive(Component, Debug)]
+pub struct PrefabInstance<T: PrefabAsset> {
+    pub handle: Handle<T>,
+    /// The tick the prefab was last applied at, components changed after this were modified at runtime.
+    applied: Option<Tick>,
+    /// Whether the prefab failed to load, so it is no longer waited on.
+    failed: bool
+}
+
+impl<T: PrefabAsset> PrefabInstance<T> {
+    pub fn new(handle: Handle<T>) -> Self {
+        Self { handle, applied: None, failed: false }
+    }
+
+    /// Whether the prefabs components have been added to the entity.
+    pub fn is_applied(&self) -> bool {
+        self.applied.is_some()
+    }
+
+    /// Whether the prefab failed to load, in which case its components are never added.
+    pub fn is_failed(&self) -> bool {
+        self.failed
+    }
+}
+
+/// Allows prefab assets to be spawned with `Commands`.
+pub trait CommandsPrefabExt {
+    /// Spawns an entity that receives the components of the prefab once its asset has loaded.
+    fn spawn_prefab<T: PrefabAsset>(&mut self, handle: Handle<T>) -> EntityCommands<'_>;
+}
+
+impl CommandsPrefabExt for Commands<'_, '_> {
+    fn spawn_prefab<T: PrefabAsset>(&mut self, handle: Handle<T>) -> EntityCommands<'_> {
+        self.spawn(PrefabInstance::new(handle))
+    }
+}
+
+/// Allows prefab assets to be registered with the `App`, done by the plugin for prefabs marked with `#[prefab(asset)]`.
+pub trait AppPrefabAssetExt {
+    /// Registers the prefab as a reflected asset with a loader, applying it to its instances as it loads and reloads.
+    fn register_prefab_asset<T: PrefabAsset>(&mut self) -> &mut Self;
+}
+
+impl AppPrefabAssetExt for App {
+    fn register_prefab_asset<T: PrefabAsset>(&mut self) -> &mut Self {
+        self.init_asset::<T>()
+            .register_asset_reflect::<T>()
+            .init_asset_loader::<PrefabLoader<T>>()
+            .add_systems(PostUpdate, apply_prefab_assets::<T>.before(TransformSystem::TransformPropagate))
+    }
+}
+
+/// Adds the prefab to new instances once it has loaded and updates instances of modified prefabs.
+/// Components that were changed since the prefab was last applied are left as they are.
+fn apply_prefab_assets<T: PrefabAsset>(world: &mut World, mut cursor: Local<EventCursor<AssetEvent<T>>>, mut checked: Local<Tick>) {
+    // the applied ticks are clamped as often as Bevy clamps component ticks, so they can not wrap around and look recent
+    let this_run = world.change_tick();
+    if this_run.get().wrapping_sub(checked.get()) > CHECK_TICK_THRESHOLD {
+        for mut instance in world.query::<&mut PrefabInstance<T>>().iter_mut(world) {
+            if let Some(applied) = &mut instance.bypass_change_detection().applied {
+                clamp_tick(applied, this_run);
+            }
+        }
+        *checked = this_run;
+    }
+
+    let modified = cursor.read(world.resource::<Events<AssetEvent<T>>>())
+        .filter_map(|event| match event {
+            AssetEvent::Modified { id } => Some(*id),
+            _ => None
+        })
+        .collect::<HashSet<_>>();
+
+    // find instances that are waiting on their prefab or whose prefab was modified
+    let instances = world.query::<(Entity, &PrefabInstance<T>)>()
+        .iter(world)
+        .filter(|(_, instance)| (instance.applied.is_none() && !instance.failed) || modified.contains(&instance.handle.id()))
+        .map(|(entity, instance)| (entity, instance.handle.id(), instance.applied))
+        .collect::<Vec<_>>();
+
+    for (entity, id, applied) in instances {
+        let Some(prefab) = world.resource::<Assets<T>>().get(id) else {
+            // stop waiting on prefabs that failed to load, reporting it once
+            let failed = world.get_resource::<AssetServer>().and_then(|server| match server.load_state(id) {
+                LoadState::Failed(error) => Some(error),
+                _ => None
+            });
+            if let Some(error) = failed {
+                error!("Prefab {} of entity {entity} failed to load: {error}", T::type_path());
+                if let Some(mut instance) = world.get_mut::<PrefabInstance<T>>(entity) {
+                    instance.failed = true;
+                }
+            }
+            continue
+        };
+
+        match applied {
+            None => {
+                let bundle = T::from_reflect(prefab.as_partial_reflect()).expect("Failed to copy prefab asset.");
+                world.entity_mut(entity).insert(bundle);
+            },
+            Some(applied) => reapply_prefab(world, entity, id, applied)
+        }
+
+        let tick = world.change_tick();
+        if let Some(mut instance) = world.get_mut::<PrefabInstance<T>>(entity) {
+            instance.applied = Some(tick);
+        }
+    }
+}
+
+/// Moves the tick forward so it is never older than the maximum age of a change tick.
+fn clamp_tick(tick: &mut Tick, this_run: Tick) {
+    if this_run.get().wrapping_sub(tick.get()) > MAX_CHANGE_AGE {
+        *tick = Tick::new(this_run.get().wrapping_sub(MAX_CHANGE_AGE));
+    }
+}
+
+/// Applies each component of the prefab to the entity, skipping components that are missing or were changed since the prefab was applied.
+fn reapply_prefab<T: PrefabAsset>(world: &mut World, entity: Entity, id: AssetId<T>, applied: Tick) {
+    let registry = world.resource::<AppTypeRegistry>().clone();
+    let registry = registry.read();
+
+    // copy out the prefab so that the world can be modified while applying it
+    let Some(prefab) = world.resource::<Assets<T>>().get(id).map(|prefab| prefab.clone_value()) else { return };
+    let ReflectRef::Struct(prefab) = prefab.reflect_ref() else { return };
+
+    let this_run = world.change_tick();
+    for field in prefab.iter_fields() {
+        let Some(type_id) = field.get_represented_type_info().map(|info| info.type_id()) else { continue };
+        let Some(reflect_component) = registry.get_type_data::<ReflectComponent>(type_id) else { continue };
+        let Some(component_id) = world.components().get_id(type_id) else { continue };
+
+        let unchanged = world.entity(entity)
+            .get_change_ticks_by_id(component_id)
+            .is_some_and(|ticks| !ticks.is_changed(applied, this_run));
+        if unchanged {
+            reflect_component.apply(world.entity_mut(entity), field);
+        }
+    }
+}
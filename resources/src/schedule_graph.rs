@@ -0,0 +1,136 @@
+use std::collections::{BTreeMap, BTreeSet};
+use bevy::{ecs::schedule::{NodeId, ScheduleGraph}, prelude::*};
+
+use crate::PluginManifest;
+
+/// The systems of a plugin in each schedule of the `App` with the orderings between them.
+/// Orderings come from `before`, `after`, `chain` and priorities, including those made through system sets.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PluginScheduleGraph {
+    pub plugin: &'static str,
+    pub schedules: Vec<PluginSchedule>
+}
+
+/// The systems of a plugin in one schedule.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PluginSchedule {
+    pub label: String,
+    pub systems: Vec<&'static str>,
+    /// Pairs of systems where the first runs before the second.
+    pub edges: Vec<(&'static str, &'static str)>
+}
+
+impl PluginScheduleGraph {
+    /// Walks the schedules of the world, keeping only the systems listed in the manifest.
+    pub fn new(world: &World, manifest: &PluginManifest) -> Self {
+        let mut schedules = world.resource::<Schedules>()
+            .iter()
+            .filter_map(|(label, schedule)| plugin_schedule(format!("{label:?}"), schedule.graph(), manifest))
+            .collect::<Vec<_>>();
+        schedules.sort_by(|a, b| a.label.cmp(&b.label));
+        Self { plugin: manifest.name, schedules }
+    }
+
+    /// Get the schedule with the given label, like `Update` or `OnEnter(Playing)`.
+    pub fn schedule(&self, label: &str) -> Option<&PluginSchedule> {
+        self.schedules.iter().find(|schedule| schedule.label == label)
+    }
+
+    /// Whether the system `before` is ordered to run before `after` in any schedule.
+    pub fn has_edge(&self, before: &str, after: &str) -> bool {
+        self.schedules.iter().any(|schedule| schedule.edges.iter().any(|edge| edge.0 == before && edge.1 == after))
+    }
+
+    /// Writes the graph in the DOT format, with a cluster for each schedule.
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph \"{}\" {{\n", escape(self.plugin));
+        for (index, schedule) in self.schedules.iter().enumerate() {
+            dot += &format!("    subgraph cluster_{index} {{\n        label=\"{}\";\n", escape(&schedule.label));
+            for system in &schedule.systems {
+                dot += &format!("        \"{index}_{system}\" [label=\"{system}\"];\n");
+            }
+            dot += "    }\n";
+            for (before, after) in &schedule.edges {
+                dot += &format!("    \"{index}_{before}\" -> \"{index}_{after}\";\n");
+            }
+        }
+        dot + "}\n"
+    }
+
+    /// Writes the graph as a Mermaid flowchart, with a subgraph for each schedule.
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = "flowchart TD\n".to_string();
+        for (index, schedule) in self.schedules.iter().enumerate() {
+            mermaid += &format!("    subgraph schedule_{index} [\"{}\"]\n", escape(&schedule.label));
+            for system in &schedule.systems {
+                mermaid += &format!("        s{index}_{system}[\"{system}\"]\n");
+            }
+            mermaid += "    end\n";
+            for (before, after) in &schedule.edges {
+                mermaid += &format!("    s{index}_{before} --> s{index}_{after}\n");
+            }
+        }
+        mermaid
+    }
+}
+
+/// Get the systems of the plugin in the schedule, or `None` if it has none there.
+fn plugin_schedule(label: String, graph: &ScheduleGraph, manifest: &PluginManifest) -> Option<PluginSchedule> {
+    // systems are named by their path, piped systems by both of their paths
+    let nodes = graph.systems()
+        .filter_map(|(id, system, _)| {
+            let name = system.name();
+            manifest.systems.iter()
+                .find(|manifest_system| {
+                    let path = format!("{}::{}", manifest.module, manifest_system.name);
+                    name == path || name.starts_with(&format!("Pipe({path}, "))
+                })
+                .map(|manifest_system| (id, manifest_system.name))
+        })
+        .collect::<BTreeMap<_, _>>();
+    if nodes.is_empty() { return None }
+
+    // orderings may be made between sets, like the set of a system function, so expand them to the systems within
+    let mut edges = BTreeSet::new();
+    for (from, to, _) in graph.dependency().graph().all_edges() {
+        for before in members(graph, from).into_iter().filter_map(|id| nodes.get(&id)) {
+            for after in members(graph, to).into_iter().filter_map(|id| nodes.get(&id)) {
+                if before != after { edges.insert((*before, *after)); }
+            }
+        }
+    }
+
+    let mut systems = nodes.into_values().collect::<Vec<_>>();
+    systems.sort();
+    Some(PluginSchedule { label, systems, edges: edges.into_iter().collect() })
+}
+
+/// Get the systems in a node, which is the system itself or every system nested in a set.
+fn members(graph: &ScheduleGraph, node: NodeId) -> Vec<NodeId> {
+    let mut systems = Vec::new();
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node.is_system() {
+            systems.push(node);
+        } else {
+            stack.extend(graph.hierarchy().graph().neighbors(node));
+        }
+    }
+    systems
+}
+
+fn escape(text: &str) -> String {
+    text.replace('"', "\\\"")
+}
+
+/// Allows the schedule graph of a plugin to be exported from the `App` once it is built.
+pub trait AppScheduleGraphExt {
+    /// Get the systems of the plugin described by the manifest in each schedule, with the orderings between them.
+    fn plugin_schedule_graph(&self, manifest: &PluginManifest) -> PluginScheduleGraph;
+}
+
+impl AppScheduleGraphExt for App {
+    fn plugin_schedule_graph(&self, manifest: &PluginManifest) -> PluginScheduleGraph {
+        PluginScheduleGraph::new(self.world(), manifest)
+    }
+}
@@ -0,0 +1,208 @@
+use std::marker::PhantomData;
+use bevy::{ecs::{event::EventCursor, system::SystemParam}, prelude::*, state::state::{FreelyMutableState, StateTransitionSteps}};
+
+/// Keeps an entity alive through scope cleanups and scene resets, even when its parent is despawned.
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component, Default)]
+pub struct ScopeGlobal;
+
+/// Marks an entity that is local to a state, added along with any local scope.
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component, Default)]
+pub struct Scoped;
+
+/// Ties an entity to a state, despawning it with its descendants when the state is exited.
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+#[require(Scoped)]
+pub struct ScopeLocal<S: States>(pub S);
+
+/// Ties an entity to several values of a state, keeping it alive while the state is any of them.
+/// The entity is despawned with its descendants when the state changes to a value that is not one of them.
+#[derive(Component, Reflect, Clone, Default)]
+#[reflect(Component)]
+#[require(Scoped)]
+pub struct ScopeLocalAny<S: States>(pub Vec<S>);
+
+/// Gives a scope to an entity and all of its descendants, used to pass the scope of a prefab on to its children.
+/// The scope may be a tuple of scopes when it is tied to several states.
+/// Entities that already have their own scope keep it, along with their descendants.
+pub struct PropagateScope<B: Bundle + Clone>(pub B);
+
+impl<B: Bundle + Clone> EntityCommand for PropagateScope<B> {
+    fn apply(self, entity: Entity, world: &mut World) {
+        let mut stack = vec![entity];
+        while let Some(entity) = stack.pop() {
+            let Ok(mut entity_mut) = world.get_entity_mut(entity) else { continue };
+            if entity_mut.contains::<ScopeGlobal>() || entity_mut.contains::<Scoped>() { continue }
+
+            entity_mut.insert(self.0.clone());
+            if let Some(children) = entity_mut.get::<Children>() {
+                stack.extend(children.iter().copied());
+            }
+        }
+    }
+}
+
+/// Despawns an entity with its descendants, except for descendants with `ScopeGlobal` which are detached and kept alive.
+pub struct DespawnScoped;
+
+impl EntityCommand for DespawnScoped {
+    fn apply(self, entity: Entity, world: &mut World) {
+        if world.get::<ScopeGlobal>(entity).is_some() { return }
+
+        // find the global descendants, which do not need to be searched further as they are kept with their descendants
+        let mut globals = Vec::new();
+        let mut stack = world.get::<Children>(entity).map(|children| children.to_vec()).unwrap_or_default();
+        while let Some(descendant) = stack.pop() {
+            if world.get::<ScopeGlobal>(descendant).is_some() {
+                globals.push(descendant);
+            } else if let Some(children) = world.get::<Children>(descendant) {
+                stack.extend(children.iter().copied());
+            }
+        }
+
+        for global in globals {
+            world.entity_mut(global).remove_parent();
+        }
+        if let Ok(entity) = world.get_entity_mut(entity) {
+            entity.despawn_recursive();
+        }
+    }
+}
+
+/// Despawns every entity local to a state, except those with `ScopeGlobal`, as if the scene was loaded again.
+/// States can also be reset to their default value, which exits their current value and so runs their scope cleanups.
+#[derive(Default)]
+pub struct SceneReset {
+    states: Vec<fn(&mut World)>
+}
+
+impl SceneReset {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Resets the given state to its default value along with the scene.
+    pub fn with_state<S: FreelyMutableState + Default>(mut self) -> Self {
+        self.states.push(|world| world.resource_mut::<NextState<S>>().set(S::default()));
+        self
+    }
+}
+
+impl Command for SceneReset {
+    fn apply(self, world: &mut World) {
+        let locals = world.query_filtered::<Entity, (With<Scoped>, Without<ScopeGlobal>)>()
+            .iter(world)
+            .collect::<Vec<_>>();
+        for entity in locals {
+            DespawnScoped.apply(entity, world);
+        }
+
+        for reset in self.states {
+            reset(world);
+        }
+    }
+}
+
+/// Gets the entities that are global or local to a state, telling apart what should be kept when a level is reloaded.
+#[derive(SystemParam)]
+pub struct ScopedEntities<'w, 's> {
+    globals: Query<'w, 's, Entity, With<ScopeGlobal>>,
+    locals: Query<'w, 's, Entity, (With<Scoped>, Without<ScopeGlobal>)>
+}
+
+impl ScopedEntities<'_, '_> {
+    /// Iterates every entity with `ScopeGlobal`.
+    pub fn global(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.globals.iter()
+    }
+
+    /// Iterates every entity local to a state that is not global.
+    pub fn local(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.locals.iter()
+    }
+
+    pub fn is_global(&self, entity: Entity) -> bool {
+        self.globals.contains(entity)
+    }
+
+    pub fn is_local(&self, entity: Entity) -> bool {
+        self.locals.contains(entity)
+    }
+}
+
+/// Despawns the entities scoped to the state `S` as its values are exited.
+/// Added by the plugin for states marked with `#[init_state]`, other states can add it themselves.
+pub struct ScopeCleanupPlugin<S: States>(PhantomData<S>);
+
+impl<S: States> Default for ScopeCleanupPlugin<S> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<S: States> Plugin for ScopeCleanupPlugin<S> {
+    fn build(&self, app: &mut App) {
+        // runs after the `OnExit` schedules so they still see the entities of the state they exit, like scoped resources do
+        app.add_systems(
+            StateTransition,
+            cleanup_scoped_entities::<S>.after(StateTransitionSteps::ExitSchedules).before(StateTransitionSteps::EnterSchedules)
+        );
+    }
+}
+
+/// Despawns the entities whose scope was exited by the last transition of the state.
+fn cleanup_scoped_entities<S: States>(
+    mut commands: Commands,
+    mut transitions: EventReader<StateTransitionEvent<S>>,
+    locals: Query<(Entity, &ScopeLocal<S>), Without<ScopeGlobal>>,
+    anys: Query<(Entity, &ScopeLocalAny<S>), Without<ScopeGlobal>>
+) {
+    // at most one transition happens each frame, without one nothing needs to be checked
+    let Some(transition) = transitions.read().last() else { return };
+    if transition.entered == transition.exited { return }
+    let Some(exited) = &transition.exited else { return };
+
+    // entities may be despawned along with a scoped parent, so they may already be gone
+    for (entity, scope) in &locals {
+        if scope.0 == *exited {
+            commands.entity(entity).queue(DespawnScoped);
+        }
+    }
+
+    let entered = transition.entered.as_ref();
+    for (entity, scope) in &anys {
+        if scope.0.contains(exited) && !entered.is_some_and(|entered| scope.0.contains(entered)) {
+            commands.entity(entity).queue(DespawnScoped);
+        }
+    }
+}
+
+/// Allows resources to be scoped to a state, done by the plugin for resources marked with `#[init_resource(scope = State::Variant)]`.
+pub trait AppScopedResourceExt {
+    /// Initializes the resource when the state is entered and removes it when the state is exited.
+    /// This happens between the `OnExit` and `OnEnter` schedules, so `OnEnter` systems can already read the resource.
+    fn init_scoped_resource<R: Resource + FromWorld, S: States>(&mut self, state: S) -> &mut Self;
+}
+
+impl AppScopedResourceExt for App {
+    fn init_scoped_resource<R: Resource + FromWorld, S: States>(&mut self, state: S) -> &mut Self {
+        let scope = move |world: &mut World, mut cursor: Local<EventCursor<StateTransitionEvent<S>>>| {
+            let Some(transition) = cursor.read(world.resource::<Events<StateTransitionEvent<S>>>()).last().cloned() else { return };
+            if transition.entered == transition.exited { return }
+
+            if transition.exited.as_ref() == Some(&state) {
+                world.remove_resource::<R>();
+            }
+            if transition.entered.as_ref() == Some(&state) {
+                world.init_resource::<R>();
+            }
+        };
+
+        self.add_systems(
+            StateTransition,
+            scope.after(StateTransitionSteps::ExitSchedules).before(StateTransitionSteps::EnterSchedules)
+        )
+    }
+}
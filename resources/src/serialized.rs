@@ -0,0 +1,130 @@
+// without a codec feature there is nothing to encode or decode with
+#![cfg_attr(not(any(feature = "json", feature = "ron", feature = "bincode")), allow(unused_variables, dead_code))]
+
+use std::{collections::HashMap, fmt::Display};
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{BoxedExecutable, Executable};
+
+/// An executable that can be sent as a tagged message, implemented by `#[executable(Type, serde)]`.
+pub trait SerdeExecutable: Executable<()> + Serialize + DeserializeOwned + Send + Sync + 'static {
+    /// The tag used to find the type of a serialized executable when it is decoded.
+    const TAG: &'static str;
+}
+
+/// The formats executables can be encoded with, each enabled by the cargo feature of the same name.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ExecutableCodec {
+    #[cfg(feature = "json")]
+    Json,
+    #[cfg(feature = "ron")]
+    Ron,
+    #[cfg(feature = "bincode")]
+    Bincode
+}
+
+impl ExecutableCodec {
+    /// Encodes the given value with this codec.
+    pub fn encode<T: Serialize>(&self, value: &T) -> Result<Vec<u8>, ExecutableCodecError> {
+        match *self {
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::to_vec(value).map_err(ExecutableCodecError::encode),
+            #[cfg(feature = "ron")]
+            Self::Ron => ron::to_string(value).map(String::into_bytes).map_err(ExecutableCodecError::encode),
+            #[cfg(feature = "bincode")]
+            Self::Bincode => bincode::serialize(value).map_err(ExecutableCodecError::encode)
+        }
+    }
+
+    /// Decodes a value of the given type from bytes encoded with this codec.
+    pub fn decode<T: DeserializeOwned>(&self, bytes: &[u8]) -> Result<T, ExecutableCodecError> {
+        match *self {
+            #[cfg(feature = "json")]
+            Self::Json => serde_json::from_slice(bytes).map_err(ExecutableCodecError::decode),
+            #[cfg(feature = "ron")]
+            Self::Ron => ron::de::from_bytes(bytes).map_err(ExecutableCodecError::decode),
+            #[cfg(feature = "bincode")]
+            Self::Bincode => bincode::deserialize(bytes).map_err(ExecutableCodecError::decode)
+        }
+    }
+}
+
+/// An error from encoding or decoding a serialized executable.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExecutableCodecError {
+    /// No executable has been registered with the tag of the message.
+    UnknownTag(String),
+    Encode(String),
+    Decode(String)
+}
+
+impl ExecutableCodecError {
+    fn encode(error: impl Display) -> Self { Self::Encode(error.to_string()) }
+    fn decode(error: impl Display) -> Self { Self::Decode(error.to_string()) }
+}
+
+impl Display for ExecutableCodecError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownTag(tag) => write!(f, "No serde executable registered with tag {tag:?}"),
+            Self::Encode(error) => write!(f, "Failed to encode executable: {error}"),
+            Self::Decode(error) => write!(f, "Failed to decode executable: {error}")
+        }
+    }
+}
+
+impl std::error::Error for ExecutableCodecError {}
+
+/// An executable encoded with its tag, ready to be sent over the network or saved for replays.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializedExecutable {
+    pub tag: String,
+    pub bytes: Vec<u8>
+}
+
+impl SerializedExecutable {
+    /// Encodes the given executable with its tag.
+    pub fn encode<T: SerdeExecutable>(codec: ExecutableCodec, executable: &T) -> Result<Self, ExecutableCodecError> {
+        Ok(Self { tag: T::TAG.to_string(), bytes: codec.encode(executable)? })
+    }
+}
+
+type DecodeFn = fn(ExecutableCodec, &[u8]) -> Result<BoxedExecutable, ExecutableCodecError>;
+
+/// Registered serde executables keyed by their tag, with the name of their type, used to decode serialized executables into the right type.
+#[derive(Resource, Default)]
+pub struct SerdeExecutables(HashMap<&'static str, (&'static str, DecodeFn)>);
+
+impl SerdeExecutables {
+    /// Registers the given executable type so that it can be decoded by its tag.
+    /// Registering a type again does nothing, but panics if another type was already registered with the same tag.
+    pub fn register<T: SerdeExecutable>(&mut self) {
+        let name = std::any::type_name::<T>();
+        if let Some((registered, _)) = self.0.get(T::TAG) {
+            if *registered == name { return }
+            panic!("Serde executables {registered} and {name} are both tagged {:?}, give one of them another tag with `serde = \"tag\"`.", T::TAG);
+        }
+        self.0.insert(T::TAG, (name, |codec, bytes| Ok(Box::new(codec.decode::<T>(bytes)?))));
+    }
+
+    /// Decodes a serialized executable into the type registered with its tag.
+    pub fn decode(&self, codec: ExecutableCodec, serialized: &SerializedExecutable) -> Result<BoxedExecutable, ExecutableCodecError> {
+        let (_, decode) = self.0.get(serialized.tag.as_str())
+            .ok_or_else(|| ExecutableCodecError::UnknownTag(serialized.tag.clone()))?;
+        decode(codec, &serialized.bytes)
+    }
+}
+
+/// Allows serde executables to be registered with the `App`.
+pub trait AppSerdeExecutableExt {
+    /// Registers the given executable type so that it can be decoded by its tag.
+    fn register_serde_executable<T: SerdeExecutable>(&mut self) -> &mut Self;
+}
+
+impl AppSerdeExecutableExt for App {
+    fn register_serde_executable<T: SerdeExecutable>(&mut self) -> &mut Self {
+        self.world_mut().get_resource_or_insert_with(SerdeExecutables::default).register::<T>();
+        self
+    }
+}
@@ -0,0 +1,72 @@
+use std::{collections::BTreeMap, sync::Mutex, time::{Duration, Instant}};
+use bevy::prelude::*;
+
+/// How long each run of a system took, collected for systems marked with `#[trace(timing)]`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SystemTiming {
+    pub min: Duration,
+    pub max: Duration,
+    pub total: Duration,
+    pub runs: u32
+}
+
+impl SystemTiming {
+    /// The average time of a run.
+    pub fn avg(&self) -> Duration {
+        self.total / self.runs.max(1)
+    }
+
+    fn record(&mut self, elapsed: Duration) {
+        self.min = self.min.min(elapsed);
+        self.max = self.max.max(elapsed);
+        self.total += elapsed;
+        self.runs += 1;
+    }
+}
+
+/// The timings of traced systems, named like `Plugin::system`.
+/// Timings are recorded through a shared reference, so timed systems can still run in parallel.
+#[derive(Resource, Debug, Default)]
+pub struct SystemTimings(Mutex<BTreeMap<&'static str, SystemTiming>>);
+
+impl SystemTimings {
+    /// Starts timing a run of the system, which is recorded when the returned timer is dropped.
+    pub fn start(&self, name: &'static str) -> SystemTimer<'_> {
+        SystemTimer { timings: self, name, start: Instant::now() }
+    }
+
+    pub fn record(&self, name: &'static str, elapsed: Duration) {
+        self.0.lock().expect("System timings were poisoned.")
+            .entry(name)
+            .or_insert(SystemTiming { min: Duration::MAX, max: Duration::ZERO, total: Duration::ZERO, runs: 0 })
+            .record(elapsed);
+    }
+
+    /// Get the timing of the system with the given name.
+    pub fn get(&self, name: &str) -> Option<SystemTiming> {
+        self.0.lock().expect("System timings were poisoned.").get(name).copied()
+    }
+
+    /// Get the timing of every system that has run, sorted by name.
+    pub fn all(&self) -> Vec<(&'static str, SystemTiming)> {
+        self.0.lock().expect("System timings were poisoned.").iter().map(|(name, timing)| (*name, *timing)).collect()
+    }
+
+    /// Clears every timing, for example to only measure the frames after loading.
+    pub fn reset(&self) {
+        self.0.lock().expect("System timings were poisoned.").clear();
+    }
+}
+
+/// Records the time since it was started into `SystemTimings` when dropped.
+pub struct SystemTimer<'a> {
+    timings: &'a SystemTimings,
+    name: &'static str,
+    start: Instant
+}
+
+impl Drop for SystemTimer<'_> {
+    fn drop(&mut self) {
+        self.timings.record(self.name, self.start.elapsed());
+    }
+}